@@ -1,50 +1,75 @@
 #![no_std]
 #![no_main]
 #![deny(unused_must_use)]
-#![feature(type_alias_impl_trait)]
-
-use core::convert::Infallible;
+#![allow(incomplete_features)]
+#![feature(async_fn_in_trait, type_alias_impl_trait)]
 
 use defmt_rtt as _;
 use embassy_executor::{Executor, _export::StaticCell};
+use embassy_futures::select::{select, select3, Either, Either3};
 use embassy_rp::{
+    flash::{Flash, ERASE_SIZE},
     gpio::{AnyPin, Input, Level, Output as EmbassyOutput, Pin, Pull},
     multicore::{spawn_core1, Stack},
-    peripherals::{PIN_10, PIN_11, PIN_12, PIN_13, PIN_14, PIN_15},
+    peripherals::{FLASH, PIN_10, PIN_11, PIN_12, PIN_13, PIN_14, PIN_15, PIN_6},
     spi::{Config, Spi},
 };
-use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
-use embassy_time::{Delay, Duration, Ticker, Timer};
-use embedded_hal::digital::v2::InputPin;
+use embassy_sync::{
+    blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel, signal::Signal,
+};
+use embassy_time::{Delay, Duration, Instant, Ticker, Timer};
 use embedded_hal_async::spi::ExclusiveDevice;
 use heapless::Vec;
 use panic_probe as _;
 use rotary_encoder_embedded::{standard::StandardMode, Direction, RotaryEncoder};
-use seq::{Frac, OutputConfig, OutputType, Rate, Seq};
+use seq::OutputType;
 use ssd1306_async::{prelude::*, Ssd1306};
-
-use crate::{
+use toad_core::{
     animator::Animator,
+    clock::{ClockIn, OnClockLoss},
     display::Display,
+    gestures::{Gestures, Timings},
     screens::Screens,
-    state::{Command, Output, Screen, ScreenState, State, StateChange},
+    sequencer::Sequencer,
+    state::{Button, Command, Output, PlayStatus, Screen, ScreenState, State, StateChange, Sync},
     state_memo::StateMemo,
+    storage::{Store, SECTORS},
 };
 
+use crate::oled::Oled;
+
+const FLASH_SIZE: usize = 2 * 1024 * 1024;
+// The last sectors of flash are kept out of `memory.x` for saved state.
+const STORAGE_OFFSET: u32 = (FLASH_SIZE - SECTORS * ERASE_SIZE) as u32;
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+// How long to keep going at the last tempo when the clock input stops.
+const FREEWHEEL: Duration = Duration::from_secs(4);
+const ON_CLOCK_LOSS: OnClockLoss = OnClockLoss::Stop;
+const LONG_PRESS: Duration = Duration::from_millis(800);
+const DOUBLE_PRESS: Duration = Duration::from_millis(300);
+const DEBOUNCE: Duration = Duration::from_millis(5);
+
 static mut CORE1_STACK: Stack<65_536> = Stack::new();
 static EXECUTOR0: StaticCell<Executor> = StaticCell::new();
 static EXECUTOR1: StaticCell<Executor> = StaticCell::new();
 static COMMAND_CHANNEL: Channel<CriticalSectionRawMutex, Command, 8> = Channel::new();
+static BUTTON_CHANNEL: Channel<CriticalSectionRawMutex, (Button, Edge, Instant), 8> =
+    Channel::new();
+static CLOCK_CHANNEL: Channel<CriticalSectionRawMutex, u64, 8> = Channel::new();
 static TICK_STATE_CHANNEL: Channel<CriticalSectionRawMutex, StateChange, 8> = Channel::new();
 static DISPLAY_STATE_CHANNEL: Channel<CriticalSectionRawMutex, StateChange, 8> = Channel::new();
+static SAVE_STATE_SIGNAL: Signal<CriticalSectionRawMutex, State> = Signal::new();
+
+#[derive(Clone, Copy)]
+enum Edge {
+    Down,
+    Up,
+}
 
 type Encoder = RotaryEncoder<StandardMode, Input<'static, PIN_14>, Input<'static, PIN_15>>;
+type FlashStore = Store<Flash<'static, FLASH, FLASH_SIZE>>;
 
-mod animator;
-mod display;
-mod screens;
-mod state;
-mod state_memo;
+mod oled;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
@@ -73,25 +98,11 @@ fn main() -> ! {
     }
     rst.set_high();
 
-    let display = Display::new(display_ctx);
+    let display = Display::new(Oled::new(display_ctx));
 
-    let mut outputs = Vec::new();
-
-    let mut gate_a_config = OutputConfig::new();
-    gate_a_config.set_output_type(OutputType::Euclid);
-    gate_a_config.set_rate(Rate::Mult(16, Frac::Zero));
-    outputs.push(gate_a_config).ok();
-
-    let gate_b_config = OutputConfig::new();
-    outputs.push(gate_b_config).ok();
-
-    let gate_c_config = OutputConfig::new();
-    outputs.push(gate_c_config).ok();
-
-    let gate_d_config = OutputConfig::new();
-    outputs.push(gate_d_config).ok();
-
-    let initial_state = State::new(outputs);
+    let mut initial_state = State::initial();
+    let mut store = Store::new(Flash::new(p.FLASH), STORAGE_OFFSET);
+    store.load(&mut initial_state);
     let initial_state1 = initial_state.clone();
 
     let outputs = {
@@ -107,6 +118,7 @@ fn main() -> ! {
         outputs
     };
 
+    let clock_in = Input::new(p.PIN_6, Pull::Down);
     let bpm_button = Input::new(p.PIN_10, Pull::Up);
     let play_button = Input::new(p.PIN_11, Pull::Up);
     let page_button = Input::new(p.PIN_12, Pull::Up);
@@ -118,8 +130,8 @@ fn main() -> ! {
         RotaryEncoder::new(rotary_dt, rotary_clk).into_standard_mode()
     };
 
-    let seq = Seq::new(initial_state.bpm.0, initial_state.outputs.clone());
-    let memo = StateMemo::new(Screen::Home);
+    let seq = Sequencer::new(&initial_state.preset());
+    let memo = StateMemo::new(&initial_state);
 
     spawn_core1(p.CORE1, unsafe { &mut CORE1_STACK }, move || {
         let executor1 = EXECUTOR1.init(Executor::new());
@@ -130,13 +142,16 @@ fn main() -> ! {
             let _ = spawner.spawn(core1_page_button_task(page_button));
             let _ = spawner.spawn(core1_play_button_task(play_button));
             let _ = spawner.spawn(core1_bpm_button_task(bpm_button));
+            let _ = spawner.spawn(core1_gestures_task());
         });
     });
 
     let executor0 = EXECUTOR0.init(Executor::new());
     executor0.run(|spawner| {
         let _ = spawner.spawn(core0_state_task(initial_state1));
+        let _ = spawner.spawn(core0_clock_task(clock_in));
         let _ = spawner.spawn(core0_tick_task(memo, seq, outputs));
+        let _ = spawner.spawn(core0_save_task(store));
     });
 }
 
@@ -146,48 +161,192 @@ async fn core0_state_task(mut state: State) {
         let command = COMMAND_CHANNEL.recv().await;
         if let Some(state_change) = state.handle_command(command) {
             TICK_STATE_CHANNEL.send(state_change).await;
+            SAVE_STATE_SIGNAL.signal(state.clone());
+        }
+    }
+}
+
+/// Flash can't be read while it's being written, which stalls both cores, so
+/// only save once the controls have been left alone for `SAVE_DELAY`.
+#[embassy_executor::task]
+async fn core0_save_task(mut store: FlashStore) {
+    loop {
+        let mut state = SAVE_STATE_SIGNAL.wait().await;
+
+        loop {
+            match select(SAVE_STATE_SIGNAL.wait(), Timer::after(SAVE_DELAY)).await {
+                Either::First(next_state) => state = next_state,
+                Either::Second(_) => break,
+            }
         }
+
+        store.save(&state).ok();
+    }
+}
+
+#[embassy_executor::task]
+async fn core0_clock_task(mut clock_in: Input<'static, PIN_6>) {
+    loop {
+        clock_in.wait_for_rising_edge().await;
+        let now = Instant::now().as_micros();
+        // Edges are only read while following the clock, so they're dropped
+        // rather than waited on when nothing is.
+        CLOCK_CHANNEL.try_send(now).ok();
     }
 }
 
 #[embassy_executor::task]
 async fn core0_tick_task(
     mut memo: StateMemo,
-    mut seq: Seq,
+    mut seq: Sequencer,
     mut outputs: Vec<EmbassyOutput<'static, AnyPin>, 4>,
 ) {
-    let tick_duration = seq.tick_duration_micros();
+    let tick_duration = memo.bpm.tick_duration_micros(seq.resolution());
     let mut ticker = Ticker::every(Duration::from_micros(tick_duration));
+    let mut clock = ClockIn::new(
+        seq.resolution(),
+        memo.ppqn,
+        FREEWHEEL.as_micros(),
+        ON_CLOCK_LOSS,
+    );
+    // What the home screen is showing of the clock, so it's only redrawn
+    // when that changes.
+    let mut clock_bpm = Option::None;
+    let mut clock_lock = Option::None;
     let mut state_changes: Vec<StateChange, 4> = Vec::new();
     let mut animator = Animator::new(seq.resolution(), 1);
+    // Where each output would be, so pausing can hold them low and resuming
+    // can put them back.
+    let mut gates = [false; 4];
+    // Ticks a tapped downbeat has already missed by the time it's handled.
+    let mut owed = 0;
 
     loop {
-        seq.tick();
-        outputs.iter_mut().enumerate().for_each(|(idx, output)| {
-            if seq.get_on_change(idx) {
-                output.toggle()
-            };
-            if seq.get_index_change(idx) {
-                let current_output = Output::into_output(idx);
-                if memo.current_screen.is_euclid(current_output) {
-                    let state_change = StateChange::Index(current_output, seq.get_index(idx));
-                    state_changes.push(state_change).ok();
+        // With an external clock, wake for edges and the ticks between them,
+        // and for state changes, which would otherwise wait on the clock.
+        let mut state_change = Option::None;
+        let ticks = match memo.sync {
+            Sync::Int => {
+                ticker.next().await;
+                while CLOCK_CHANNEL.try_recv().is_ok() {}
+                1 + core::mem::take(&mut owed)
+            }
+            Sync::Ext => {
+                let deadline = clock.deadline().map_or(Instant::MAX, Instant::from_micros);
+                match select3(
+                    CLOCK_CHANNEL.recv(),
+                    Timer::at(deadline),
+                    TICK_STATE_CHANNEL.recv(),
+                )
+                .await
+                {
+                    Either3::First(edge) => clock.edge(edge),
+                    Either3::Second(_) => clock.poll(Instant::now().as_micros()),
+                    Either3::Third(next_state_change) => {
+                        state_change = Option::Some(next_state_change);
+                        0
+                    }
                 }
             }
-        });
+        };
+
+        // Paused or stopped, the clock still runs but the sequences don't.
+        let ticks = match memo.play_status {
+            PlayStatus::Playing => ticks,
+            PlayStatus::Paused | PlayStatus::Stopped => 0,
+        };
+
+        for _ in 0..ticks {
+            let toggles = seq.tick();
+            outputs.iter_mut().enumerate().for_each(|(idx, output)| {
+                if toggles[idx] {
+                    gates[idx] = !gates[idx];
+                    output.toggle()
+                };
+                if seq.get_index_change(idx) {
+                    let current_output = Output::into_output(idx);
+                    if memo.current_screen.is_euclid(current_output) {
+                        let state_change = StateChange::Index(current_output, seq.get_index(idx));
+                        state_changes.push(state_change).ok();
+                    }
+                }
+            });
 
-        animator.update();
-        if animator.next_frame() {
-            state_changes.push(StateChange::Frame).ok();
+            animator.update();
+            if animator.next_frame() {
+                state_changes.push(StateChange::Frame).ok();
+            }
+        }
+
+        if let (Sync::Ext, Screen::Home) = (memo.sync, memo.current_screen) {
+            if clock.bpm() != clock_bpm {
+                clock_bpm = clock.bpm();
+                if let Option::Some(bpm) = clock_bpm {
+                    state_changes.push(StateChange::Bpm(bpm)).ok();
+                }
+            }
+            if Option::Some(clock.lock()) != clock_lock {
+                clock_lock = Option::Some(clock.lock());
+                state_changes.push(StateChange::Lock(clock.lock())).ok();
+            }
         }
 
-        while let Ok(state_change) = TICK_STATE_CHANNEL.try_recv() {
+        while let Option::Some(state_change) = state_change
+            .take()
+            .or_else(|| TICK_STATE_CHANNEL.try_recv().ok())
+        {
+            let following = matches!(memo.sync, Sync::Ext);
             memo.update(&state_change);
+            clock.set_ppqn(memo.ppqn);
+            if !following && matches!(memo.sync, Sync::Ext) {
+                // Start following from the next edge, not from any left
+                // over from before.
+                while CLOCK_CHANNEL.try_recv().is_ok() {}
+                clock = ClockIn::new(
+                    seq.resolution(),
+                    memo.ppqn,
+                    FREEWHEEL.as_micros(),
+                    ON_CLOCK_LOSS,
+                );
+            }
+            if let StateChange::NextScreen(_) | StateChange::Sync(_) = state_change {
+                // The home screen redraws with the internal tempo and no lock.
+                clock_bpm = Option::None;
+                clock_lock = Option::None;
+            }
             state_change.update_seq(&mut seq);
             let state_change = state_change.update_index(&seq);
 
-            if let StateChange::Bpm(_) = state_change {
-                let tick_duration = seq.tick_duration_micros();
+            match state_change {
+                StateChange::PlayStatus(_, PlayStatus::Playing) => {
+                    outputs.iter_mut().zip(gates).for_each(|(output, gate)| {
+                        output.set_level(gate.into());
+                    });
+                }
+                StateChange::PlayStatus(..) => outputs.iter_mut().for_each(|output| {
+                    output.set_low();
+                }),
+                StateChange::Stop(..) | StateChange::Downbeat(..) => {
+                    if let StateChange::Downbeat(_, at) = state_change {
+                        let late = Instant::now().as_micros().saturating_sub(at);
+                        owed = (late / memo.bpm.tick_duration_micros(seq.resolution())) as u32;
+                    }
+                    gates = [false; 4];
+                    outputs.iter_mut().for_each(|output| output.set_low());
+                    if let Screen::Output(output, OutputType::Euclid) = memo.current_screen {
+                        let index = seq.get_index(output.into());
+                        state_changes.push(StateChange::Index(output, index)).ok();
+                    }
+                }
+                _ => {}
+            }
+
+            if let StateChange::Bpm(_)
+            | StateChange::Downbeat(..)
+            | StateChange::Preset(_)
+            | StateChange::Sync(_) = state_change
+            {
+                let tick_duration = memo.bpm.tick_duration_micros(seq.resolution());
                 ticker = Ticker::every(Duration::from_micros(tick_duration));
             };
 
@@ -203,72 +362,102 @@ async fn core0_tick_task(
                 .map_err(|_| panic!("display state channel full; index change"))
                 .ok();
         }
-
-        ticker.next().await
     }
 }
 
 #[embassy_executor::task]
 async fn core1_encoder_button_task(encoder_button: Input<'static, PIN_13>) {
-    debounced_button(encoder_button, Command::EncoderPress).await
+    button_edges(encoder_button, Button::Encoder).await
 }
 
 #[embassy_executor::task]
 async fn core1_page_button_task(page_button: Input<'static, PIN_12>) {
-    debounced_button(page_button, Command::PagePress).await
+    button_edges(page_button, Button::Page).await
 }
 
 #[embassy_executor::task]
 async fn core1_play_button_task(play_button: Input<'static, PIN_11>) {
-    debounced_button(play_button, Command::PlayPress).await
+    button_edges(play_button, Button::Play).await
 }
 
 #[embassy_executor::task]
 async fn core1_bpm_button_task(bpm_button: Input<'static, PIN_10>) {
-    debounced_button(bpm_button, Command::BpmPress).await
+    button_edges(bpm_button, Button::Bpm).await
 }
 
-async fn debounced_button<B: InputPin>(button: B, command: Command)
-where
-    B: InputPin<Error = Infallible>,
-{
-    let mut armed = true;
-    let button_update_duration = Duration::from_micros(50_000);
+/// Waits on the pin's interrupt rather than polling, and only believes an
+/// edge if the pin still agrees once it's had `DEBOUNCE` to settle.
+async fn button_edges<T: Pin>(mut pin: Input<'static, T>, button: Button) {
+    loop {
+        pin.wait_for_falling_edge().await;
+        let at = Instant::now();
+        Timer::after(DEBOUNCE).await;
+        if pin.is_high() {
+            continue
+        }
+        let _ = BUTTON_CHANNEL.send((button, Edge::Down, at)).await;
+
+        loop {
+            pin.wait_for_high().await;
+            let at = Instant::now();
+            Timer::after(DEBOUNCE).await;
+            if pin.is_high() {
+                let _ = BUTTON_CHANNEL.send((button, Edge::Up, at)).await;
+                break
+            }
+        }
+    }
+}
+
+#[embassy_executor::task]
+async fn core1_gestures_task() {
+    let mut gestures = Gestures::new(Timings {
+        long_press: LONG_PRESS.as_micros(),
+        double_press: DOUBLE_PRESS.as_micros(),
+    });
 
     loop {
-        if armed && button.is_low().unwrap() {
-            armed = false;
+        let deadline = gestures
+            .deadline()
+            .map_or(Instant::MAX, Instant::from_micros);
+        let command = match select(BUTTON_CHANNEL.recv(), Timer::at(deadline)).await {
+            Either::First((button, Edge::Down, at)) => gestures.down(button, at.as_micros()),
+            Either::First((button, Edge::Up, at)) => gestures.up(button, at.as_micros()),
+            Either::Second(_) => gestures.poll(Instant::now().as_micros()),
+        };
+
+        if let Option::Some(command) = command {
             let _ = COMMAND_CHANNEL.send(command).await;
-        } else if !armed && button.is_high().unwrap() {
-            armed = true;
         }
-
-        Timer::after(button_update_duration).await
     }
 }
 
 #[embassy_executor::task]
 async fn core1_encoder_task(mut encoder: Encoder) {
     loop {
+        let (dt, clk) = encoder.borrow_pins();
+        select(dt.wait_for_any_edge(), clk.wait_for_any_edge()).await;
+        let now = Instant::now();
+
         encoder.update();
         match encoder.direction() {
-            Direction::Clockwise => COMMAND_CHANNEL.send(Command::EncoderRight).await,
-            Direction::Anticlockwise => COMMAND_CHANNEL.send(Command::EncoderLeft).await,
+            Direction::Clockwise => COMMAND_CHANNEL.send(Command::EncoderRight(now)).await,
+            Direction::Anticlockwise => COMMAND_CHANNEL.send(Command::EncoderLeft(now)).await,
             Direction::None => {}
         }
-
-        Timer::after(Duration::from_micros(1111)).await
     }
 }
 
 #[embassy_executor::task]
-async fn core1_display_task(state: State, mut display: Display) {
+async fn core1_display_task(state: State, mut display: Display<Oled>) {
     let mut screens = Screens::new();
 
     display.init().await;
     let next_screen = StateChange::NextScreen(ScreenState::new_home(
         state.bpm,
         state.sync,
+        state.ppqn,
+        state.swing,
         state.play_status,
     ));
     screens.draw(next_screen, &mut display);
@@ -277,6 +466,6 @@ async fn core1_display_task(state: State, mut display: Display) {
     loop {
         let state_change = DISPLAY_STATE_CHANNEL.recv().await;
         screens.draw(state_change, &mut display);
-        display.flush().await
+        display.flush().await;
     }
 }
@@ -0,0 +1,73 @@
+use embassy_rp::{
+    gpio::Output,
+    peripherals::{PIN_16, PIN_17, SPI0},
+    spi::{Async, Spi},
+};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    primitives::Rectangle,
+    Pixel,
+};
+use embedded_hal_async::spi::ExclusiveDevice;
+use ssd1306_async::{
+    mode::{BufferedGraphicsMode, DisplayConfig},
+    prelude::{DisplaySize128x64, SPIInterface},
+    Ssd1306,
+};
+use toad_core::display::Flush;
+
+pub type Ssd1306Display = Ssd1306<
+    SPIInterface<
+        ExclusiveDevice<Spi<'static, SPI0, Async>, Output<'static, PIN_17>>,
+        Output<'static, PIN_16>,
+    >,
+    DisplaySize128x64,
+    BufferedGraphicsMode<DisplaySize128x64>,
+>;
+
+/// The SSD1306 on SPI0, wrapped so it can be handed to `Display`.
+pub struct Oled(Ssd1306Display);
+
+impl Oled {
+    pub fn new(display: Ssd1306Display) -> Self {
+        Self(display)
+    }
+}
+
+impl OriginDimensions for Oled {
+    fn size(&self) -> Size {
+        OriginDimensions::size(&self.0)
+    }
+}
+
+impl DrawTarget for Oled {
+    type Color = BinaryColor;
+    type Error = <Ssd1306Display as DrawTarget>::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.0.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.0.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        DrawTarget::clear(&mut self.0, color)
+    }
+}
+
+impl Flush for Oled {
+    async fn init(&mut self) {
+        self.0.init().await.ok();
+    }
+
+    async fn flush(&mut self) {
+        self.0.flush().await.ok();
+    }
+}
@@ -1,11 +0,0 @@
-use defmt::Format;
-
-#[derive(Clone, Copy, Format)]
-pub enum Command {
-    EncoderRight,
-    EncoderLeft,
-    EncoderPress,
-    PagePress,
-    PlayPress,
-    BpmPress,
-}
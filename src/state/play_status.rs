@@ -1,7 +0,0 @@
-use defmt::Format;
-
-#[derive(Clone, Copy, Format)]
-pub enum PlayStatus {
-    Playing,
-    Paused,
-}
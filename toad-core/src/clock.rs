@@ -0,0 +1,387 @@
+//! Follows an external clock. Each rising edge is a pulse, `ppqn` of them to
+//! a beat, and `Seq`'s ticks for a pulse are spread out up to where the next
+//! edge is predicted to land.
+//!
+//! Tracking is a small PLL: the period is smoothed towards each measured
+//! interval and every pulse is anchored partway between the predicted and
+//! the actual edge, so jitter is spread over a few pulses instead of landing
+//! on the ticks. Any ticks a pulse still owes when its edge arrives are run
+//! straight away, which re-aligns the phase on every edge, and a pulse never
+//! runs ahead of its edge. An edge far enough off the prediction is taken as
+//! a new tempo and tracking starts again from it.
+//!
+//! When an edge is half a period late the clock counts as stopped: pulses
+//! carry on at the last period for `freewheel`, then it's lost and what
+//! happens depends on `OnClockLoss`. The next edge locks straight back on.
+//!
+//! Times are microseconds from any fixed point.
+
+use crate::state::{Bpm, Lock, Ppqn, MAX_BPM, MIN_BPM};
+
+// A minute in microseconds, times ten for tempos in tenths of a BPM.
+const TENTHS_IN_A_MINUTE: u64 = 600_000_000;
+// Each measured interval moves the period this fraction of the way.
+const PERIOD_SMOOTHING: i64 = 4;
+// Each edge moves the phase this fraction of the way from the prediction.
+const PHASE_SMOOTHING: i64 = 2;
+// Edges further than a period over this off the prediction start again.
+const RESNAP: u64 = 4;
+// The average error is over roughly this many edges.
+const STATS_WINDOW: i64 = 16;
+
+#[derive(Clone, Copy)]
+pub enum OnClockLoss {
+    Stop,
+    /// Keep running on our own at the last tempo.
+    Internal,
+}
+
+/// How far edges land from where they were predicted, in microseconds.
+/// Positive errors are late edges. Reset whenever tracking starts again.
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LockStats {
+    pub edges: u32,
+    pub last_error: i64,
+    pub average_error: u64,
+    pub max_error: u64,
+}
+
+impl LockStats {
+    fn record(&mut self, error: i64) {
+        let abs_error = error.unsigned_abs();
+        self.average_error = if self.edges == 0 {
+            abs_error
+        } else {
+            let average = self.average_error as i64;
+            (average + (abs_error as i64 - average) / STATS_WINDOW) as u64
+        };
+        self.edges += 1;
+        self.last_error = error;
+        self.max_error = self.max_error.max(abs_error);
+    }
+}
+
+pub struct ClockIn {
+    resolution: u32,
+    ppqn: Ppqn,
+    freewheel: u64,
+    on_loss: OnClockLoss,
+    lock: Lock,
+    freewheel_from: u64,
+    /// Where the current pulse started, real or not.
+    anchor: Option<u64>,
+    last_edge: Option<u64>,
+    period: Option<u64>,
+    ticks_left: u32,
+    stats: LockStats,
+}
+
+impl ClockIn {
+    pub fn new(resolution: u32, ppqn: Ppqn, freewheel: u64, on_loss: OnClockLoss) -> Self {
+        Self {
+            resolution,
+            ppqn,
+            freewheel,
+            on_loss,
+            lock: Lock::Lost,
+            freewheel_from: 0,
+            anchor: Option::None,
+            last_edge: Option::None,
+            period: Option::None,
+            ticks_left: 0,
+            stats: LockStats::default(),
+        }
+    }
+
+    pub fn lock(&self) -> Lock {
+        self.lock
+    }
+
+    pub fn stats(&self) -> LockStats {
+        self.stats
+    }
+
+    pub fn set_ppqn(&mut self, ppqn: Ppqn) {
+        if self.ppqn != ppqn {
+            self.ppqn = ppqn;
+            self.period = Option::None;
+            self.ticks_left = 0;
+            self.stats = LockStats::default();
+        }
+    }
+
+    /// Where the next edge should land, once there's a period to go on.
+    pub fn predicted_edge(&self) -> Option<u64> {
+        Option::Some(self.anchor? + self.period?)
+    }
+
+    /// Takes the time of a rising edge and returns how many ticks to run
+    /// now: whatever the last pulse still owed plus the first of this one.
+    pub fn edge(&mut self, now: u64) -> u32 {
+        // Only track between real edges; after a gap keep the old period
+        // until the next one.
+        let anchor = match (self.lock, self.last_edge, self.predicted_edge()) {
+            (Lock::Locked, Option::Some(last_edge), Option::Some(predicted)) => {
+                self.track(now, now.saturating_sub(last_edge), predicted)
+            }
+            (Lock::Locked, Option::Some(last_edge), Option::None) => {
+                self.period = nonzero(now.saturating_sub(last_edge));
+                now
+            }
+            _ => {
+                self.stats = LockStats::default();
+                now
+            }
+        };
+        self.last_edge = Option::Some(now);
+        self.lock = Lock::Locked;
+
+        let owed = self.ticks_left;
+        owed + self.pulse(anchor)
+    }
+
+    /// When `poll` next has something to do, or `None` if nothing happens
+    /// until the next edge.
+    pub fn deadline(&self) -> Option<u64> {
+        let anchor = self.anchor?;
+        let period = self.period?;
+
+        if self.stopped() {
+            return Option::None
+        }
+        if self.ticks_left > 0 {
+            return Option::Some(self.tick_at(anchor, period))
+        }
+
+        match self.lock {
+            Lock::Locked => Option::Some(anchor + period + period / 2),
+            Lock::Freewheel => {
+                Option::Some((anchor + period).min(self.freewheel_from + self.freewheel))
+            }
+            Lock::Lost => Option::Some(anchor + period),
+        }
+    }
+
+    /// Moves on to `now` without an edge and returns how many ticks to run.
+    pub fn poll(&mut self, now: u64) -> u32 {
+        let (Option::Some(anchor), Option::Some(period)) = (self.anchor, self.period) else {
+            return 0
+        };
+
+        if self.lock == Lock::Freewheel && now >= self.freewheel_from + self.freewheel {
+            self.lock = Lock::Lost;
+            if self.stopped() {
+                self.ticks_left = 0;
+            }
+        }
+        if self.stopped() {
+            return 0
+        }
+
+        if self.ticks_left > 0 {
+            if now < self.tick_at(anchor, period) {
+                return 0
+            }
+            self.ticks_left -= 1;
+            return 1
+        }
+
+        match self.lock {
+            Lock::Locked if now >= anchor + period + period / 2 => {
+                self.lock = Lock::Freewheel;
+                self.freewheel_from = now;
+                self.pulse(anchor + period)
+            }
+            Lock::Freewheel | Lock::Lost if now >= anchor + period => self.pulse(anchor + period),
+            _ => 0,
+        }
+    }
+
+    /// The tempo being followed.
+    pub fn bpm(&self) -> Option<Bpm> {
+        let micros_per_beat = self.period? * self.ppqn.pulses() as u64;
+        let tenths = (TENTHS_IN_A_MINUTE / micros_per_beat) as u32;
+
+        Option::Some(Bpm(tenths.clamp(MIN_BPM * 10, MAX_BPM * 10)))
+    }
+
+    /// Updates the period and phase from an edge and returns where its pulse
+    /// starts.
+    fn track(&mut self, now: u64, interval: u64, predicted: u64) -> u64 {
+        let period = self.period.unwrap_or(interval);
+        let error = now as i64 - predicted as i64;
+
+        if error.unsigned_abs() > period / RESNAP {
+            self.period = nonzero(interval);
+            self.stats = LockStats::default();
+            return now
+        }
+
+        let period = period as i64;
+        self.period = nonzero((period + (interval as i64 - period) / PERIOD_SMOOTHING) as u64);
+        self.stats.record(error);
+
+        (predicted as i64 + error / PHASE_SMOOTHING) as u64
+    }
+
+    /// Starts a pulse at `at`, running its first tick.
+    fn pulse(&mut self, at: u64) -> u32 {
+        self.anchor = Option::Some(at);
+        self.ticks_left = self.ticks_per_pulse() - 1;
+
+        1
+    }
+
+    fn stopped(&self) -> bool {
+        matches!((self.lock, self.on_loss), (Lock::Lost, OnClockLoss::Stop))
+    }
+
+    fn tick_at(&self, anchor: u64, period: u64) -> u64 {
+        let ticks_per_pulse = self.ticks_per_pulse() as u64;
+        let done = ticks_per_pulse - self.ticks_left as u64;
+
+        anchor + period * done / ticks_per_pulse
+    }
+
+    fn ticks_per_pulse(&self) -> u32 {
+        (self.resolution / self.ppqn.pulses()).max(1)
+    }
+}
+
+fn nonzero(period: u64) -> Option<u64> {
+    Option::Some(period).filter(|period| *period > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 125 BPM at 24 PPQN, with 4 ticks to a pulse.
+    const PERIOD: u64 = 20_000;
+    const FREEWHEEL: u64 = 100_000;
+
+    fn clock(on_loss: OnClockLoss) -> ClockIn {
+        ClockIn::new(96, Ppqn::P24, FREEWHEEL, on_loss)
+    }
+
+    /// Polls whenever the clock asks to, up to `until`, and returns how many
+    /// ticks it ran.
+    fn run(clock: &mut ClockIn, until: u64) -> u32 {
+        let mut now = 0;
+        let mut ticks = 0;
+        while let Option::Some(at) = clock.deadline().filter(|at| *at <= until) {
+            now = at.max(now);
+            ticks += clock.poll(now);
+        }
+
+        ticks
+    }
+
+    /// Three steady edges: locked, with a period and one tracked edge.
+    fn locked(on_loss: OnClockLoss) -> ClockIn {
+        let mut clock = clock(on_loss);
+        for edge in 0..3 {
+            clock.edge(edge * PERIOD);
+        }
+
+        clock
+    }
+
+    #[test]
+    fn locks_on_the_first_edge_and_times_from_the_second() {
+        let mut clock = clock(OnClockLoss::Stop);
+
+        assert_eq!(clock.edge(0), 1);
+        assert!(clock.lock() == Lock::Locked);
+        assert!(clock.bpm().is_none());
+        assert_eq!(clock.deadline(), Option::None);
+
+        // The first pulse's other ticks couldn't be spread without a period.
+        assert_eq!(clock.edge(PERIOD), 3 + 1);
+        assert!(clock.bpm() == Option::Some(Bpm(1_250)));
+        assert_eq!(clock.stats().edges, 0);
+        assert_eq!(run(&mut clock, 2 * PERIOD - 1), 3);
+
+        assert_eq!(clock.edge(2 * PERIOD), 1);
+        assert_eq!(clock.stats().edges, 1);
+        assert_eq!(clock.stats().last_error, 0);
+    }
+
+    #[test]
+    fn spreads_a_pulse_over_its_period() {
+        let mut clock = locked(OnClockLoss::Stop);
+
+        assert_eq!(clock.deadline(), Option::Some(2 * PERIOD + 5_000));
+        assert_eq!(clock.poll(2 * PERIOD + 4_999), 0);
+        assert_eq!(clock.poll(2 * PERIOD + 5_000), 1);
+        assert_eq!(run(&mut clock, 3 * PERIOD - 1), 2);
+        assert_eq!(clock.edge(3 * PERIOD), 1);
+    }
+
+    #[test]
+    fn smooths_jitter_into_period_and_phase() {
+        let mut clock = locked(OnClockLoss::Stop);
+
+        // 400µs late: the period moves a quarter of the way to the 20_400µs
+        // interval and the pulse starts half way to the edge.
+        clock.edge(3 * PERIOD + 400);
+        assert_eq!(clock.predicted_edge(), Option::Some(60_200 + 20_100));
+
+        // 300µs early against that: a 19_600µs interval.
+        clock.edge(4 * PERIOD);
+        assert_eq!(clock.predicted_edge(), Option::Some(80_150 + 19_975));
+        assert!(clock.bpm() == Option::Some(Bpm(1_251)));
+
+        let stats = clock.stats();
+        assert_eq!(stats.edges, 3);
+        assert_eq!(stats.last_error, -300);
+        assert_eq!(stats.max_error, 400);
+        // 0, then 400 / 16, then 25 + (300 - 25) / 16.
+        assert_eq!(stats.average_error, 42);
+    }
+
+    #[test]
+    fn starts_again_from_an_edge_over_a_quarter_period_off() {
+        let mut clock = locked(OnClockLoss::Stop);
+        clock.edge(3 * PERIOD + PERIOD / 4 + 1);
+
+        assert_eq!(clock.stats().edges, 0);
+        assert_eq!(clock.predicted_edge(), Option::Some(65_001 + 25_001));
+    }
+
+    #[test]
+    fn freewheels_then_stops_on_clock_loss() {
+        let mut clock = locked(OnClockLoss::Stop);
+
+        assert_eq!(run(&mut clock, 3 * PERIOD + PERIOD / 2 - 1), 3);
+        assert!(clock.lock() == Lock::Locked);
+
+        // Half a period late it carries on from where the edge should have
+        // been, a pulse a period.
+        assert_eq!(run(&mut clock, 8 * PERIOD - 1), 5 * 4);
+        assert!(clock.lock() == Lock::Freewheel);
+
+        // Lost 100ms after it stopped, half way through a pulse.
+        assert_eq!(run(&mut clock, 1_000_000), 2);
+        assert!(clock.lock() == Lock::Lost);
+        assert_eq!(clock.deadline(), Option::None);
+        assert_eq!(clock.poll(2_000_000), 0);
+
+        assert_eq!(clock.edge(2_000_000), 1);
+        assert!(clock.lock() == Lock::Locked);
+    }
+
+    #[test]
+    fn freewheels_then_runs_on_internally_on_clock_loss() {
+        let mut clock = locked(OnClockLoss::Internal);
+
+        assert_eq!(run(&mut clock, 8 * PERIOD - 1), 3 + 5 * 4);
+        assert!(clock.lock() == Lock::Freewheel);
+
+        // The pulse it was lost in finishes and they keep coming.
+        assert_eq!(run(&mut clock, 13 * PERIOD - 1), 4 + 4 * 4);
+        assert!(clock.lock() == Lock::Lost);
+        assert!(clock.deadline().is_some());
+    }
+}
@@ -1,10 +1,5 @@
 use core::fmt::Write;
 
-use embassy_rp::{
-    gpio::Output,
-    peripherals::{PIN_16, PIN_17, SPI0},
-    spi::Spi,
-};
 use embedded_graphics::{
     draw_target::DrawTarget,
     geometry::Dimensions,
@@ -14,13 +9,7 @@ use embedded_graphics::{
     primitives::Rectangle,
     Drawable,
 };
-use embedded_hal_async::spi::ExclusiveDevice;
 use heapless::String;
-use ssd1306_async::{
-    mode::{BufferedGraphicsMode, DisplayConfig},
-    prelude::{DisplaySize128x64, SPIInterface},
-    Ssd1306,
-};
 
 use self::{
     bmps::{Bmp, Bmps},
@@ -32,24 +21,26 @@ mod bmps;
 mod fonts;
 mod tile_grids;
 
-pub type Ssd1306Display = Ssd1306<
-    SPIInterface<
-        ExclusiveDevice<Spi<'static, SPI0, embassy_rp::spi::Async>, Output<'static, PIN_17>>,
-        Output<'static, PIN_16>,
-    >,
-    DisplaySize128x64,
-    BufferedGraphicsMode<DisplaySize128x64>,
->;
+/// Anything the screens can be drawn on.
+pub trait Canvas: DrawTarget<Color = BinaryColor> {}
+
+impl<T: DrawTarget<Color = BinaryColor>> Canvas for T {}
+
+/// Panels that are drawn on through a buffer which then has to be sent.
+pub trait Flush {
+    async fn init(&mut self);
+    async fn flush(&mut self);
+}
 
-pub struct Display {
+pub struct Display<D> {
     bmps: Bmps,
-    display: Ssd1306Display,
+    display: D,
     fonts: Fonts,
     tile_grids: TileGrids,
 }
 
-impl Display {
-    pub fn new(display: Ssd1306Display) -> Self {
+impl<D: Canvas> Display<D> {
+    pub fn new(display: D) -> Self {
         Self {
             bmps: Bmps::new(),
             display,
@@ -58,16 +49,12 @@ impl Display {
         }
     }
 
-    pub async fn init(&mut self) {
-        self.display.init().await.ok();
+    pub fn inner(&self) -> &D {
+        &self.display
     }
 
     pub fn clear(&mut self) {
-        self.display.clear();
-    }
-
-    pub async fn flush(&mut self) {
-        self.display.flush().await.ok();
+        self.display.clear(BinaryColor::Off).ok();
     }
 
     pub fn clear_caret(&mut self, point: Point) {
@@ -166,13 +153,13 @@ impl Display {
         self.clear_rect(bb);
     }
 
-    pub fn draw_smol_text<const N: usize, D>(
+    pub fn draw_smol_text<const N: usize, T>(
         &mut self,
         string: &mut String<N>,
-        displayable: D,
+        displayable: T,
         point: Point,
     ) where
-        D: core::fmt::Display,
+        T: core::fmt::Display,
     {
         string.clear();
         write!(string, "{}", displayable).unwrap();
@@ -180,7 +167,14 @@ impl Display {
         self.fonts
             .smol_text(string, point)
             .draw(&mut self.display)
-            .unwrap();
+            .ok();
+    }
+
+    pub fn smol_text_width<S: AsRef<str>>(&self, str: S) -> u32 {
+        self.fonts
+            .smol_font_bounding_box(str.as_ref(), Point::zero())
+            .size
+            .width
     }
 
     pub fn clear_bigge_text<S: AsRef<str>>(&mut self, str: S, point: Point) {
@@ -188,13 +182,13 @@ impl Display {
         self.clear_rect(bb);
     }
 
-    pub fn draw_bigge_text<const N: usize, D>(
+    pub fn draw_bigge_text<const N: usize, T>(
         &mut self,
         string: &mut String<N>,
-        displayable: D,
+        displayable: T,
         point: Point,
     ) where
-        D: core::fmt::Display,
+        T: core::fmt::Display,
     {
         string.clear();
         write!(string, "{}", displayable).unwrap();
@@ -202,7 +196,7 @@ impl Display {
         self.fonts
             .bigge_text(string, point)
             .draw(&mut self.display)
-            .unwrap();
+            .ok();
     }
 
     fn clear_bmp(&mut self, bmp: &Bmp, point: Point) {
@@ -231,3 +225,13 @@ impl Display {
         Image::new(&sub_bmp, point).draw(&mut self.display).ok();
     }
 }
+
+impl<D: Canvas + Flush> Display<D> {
+    pub async fn init(&mut self) {
+        self.display.init().await;
+    }
+
+    pub async fn flush(&mut self) {
+        self.display.flush().await;
+    }
+}
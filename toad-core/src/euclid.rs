@@ -0,0 +1,56 @@
+//! Euclid patterns, made here rather than by `seq` so they can be longer
+//! than its 16 steps.
+
+use heapless::Vec;
+use seq::{Density, Length};
+
+use crate::state::MAX_LENGTH;
+
+/// Spreads `density` onsets as evenly as they go over `length` steps,
+/// starting with one.
+pub fn euclid(density: Density, length: Length, sequence: &mut Vec<bool, MAX_LENGTH>) {
+    sequence.clear();
+    let length = (length.0 as usize).min(MAX_LENGTH);
+    let density = (density.0 as usize).min(length);
+    for idx in 0..length {
+        sequence.push(idx * density % length < density).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(density: u32, length: u32) -> Vec<bool, MAX_LENGTH> {
+        let mut sequence = Vec::new();
+        euclid(Density(density), Length(length), &mut sequence);
+        sequence
+    }
+
+    fn onsets(sequence: &[bool]) -> usize {
+        sequence.iter().filter(|on| **on).count()
+    }
+
+    #[test]
+    fn spreads_onsets_evenly() {
+        let x = true;
+        let o = false;
+        assert_eq!(pattern(3, 8), [x, o, o, x, o, o, x, o]);
+        assert_eq!(pattern(5, 8), [x, o, x, o, x, x, o, x]);
+        assert_eq!(pattern(4, 16).as_slice(), [[x, o, o, o]; 4].concat());
+    }
+
+    #[test]
+    fn plays_up_to_the_longest_length() {
+        let sequence = pattern(17, 64);
+        assert_eq!(sequence.len(), 64);
+        assert_eq!(onsets(&sequence), 17);
+        assert!(sequence[0]);
+    }
+
+    #[test]
+    fn caps_density_at_the_length() {
+        assert_eq!(onsets(&pattern(9, 5)), 5);
+        assert_eq!(onsets(&pattern(0, 5)), 0);
+    }
+}
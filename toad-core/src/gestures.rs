@@ -0,0 +1,357 @@
+//! Turns button downs and ups into `Command`s.
+//!
+//! A press is sent when the button comes back up, unless it was held for
+//! `long_press`, which sends a long press as soon as the time is up. Buttons
+//! with a double press hold their press back for `double_press` after it
+//! comes up: if the button goes down again in that time the two are sent as
+//! a double press, otherwise the press goes out late. Pressing a button while
+//! another is still down is a chord of the two, and neither sends anything
+//! else until it's released.
+//!
+//! Times are microseconds from any fixed point.
+
+use embassy_time::Instant;
+
+use crate::state::{Button, Command};
+
+#[derive(Clone, Copy)]
+pub struct Timings {
+    pub long_press: u64,
+    pub double_press: u64,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            long_press: 800_000,
+            double_press: 300_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Held {
+    Up,
+    /// `used` once the press has become a long press or a chord.
+    Down {
+        since: u64,
+        used: bool,
+    },
+}
+
+/// A press held back in case it's the first of a double press.
+#[derive(Clone, Copy)]
+struct Pending {
+    since: u64,
+    until: u64,
+}
+
+pub struct Gestures {
+    timings: Timings,
+    held: [Held; 4],
+    pending: [Option<Pending>; 4],
+}
+
+impl Gestures {
+    pub fn new(timings: Timings) -> Self {
+        Self {
+            timings,
+            held: [Held::Up; 4],
+            pending: [Option::None; 4],
+        }
+    }
+
+    pub fn down(&mut self, button: Button, now: u64) -> Option<Command> {
+        let other = Button::ALL.into_iter().find(|other| {
+            *other != button
+                && matches!(
+                    self.held[usize::from(*other)],
+                    Held::Down { used: false, .. }
+                )
+        });
+
+        match other {
+            Option::Some(other) => {
+                self.held[usize::from(other)] = Held::Down {
+                    since: now,
+                    used: true,
+                };
+                self.held[usize::from(button)] = Held::Down {
+                    since: now,
+                    used: true,
+                };
+                Option::Some(Command::Chord(other, button))
+            }
+            Option::None => {
+                self.held[usize::from(button)] = Held::Down {
+                    since: now,
+                    used: false,
+                };
+                Option::None
+            }
+        }
+    }
+
+    pub fn up(&mut self, button: Button, now: u64) -> Option<Command> {
+        let idx = usize::from(button);
+        let held = self.held[idx];
+        self.held[idx] = Held::Up;
+
+        let Held::Down { since, used: false } = held else {
+            return Option::None
+        };
+        if now.saturating_sub(since) >= self.timings.long_press {
+            return Option::Some(Command::LongPress(button))
+        }
+
+        if !button.double_presses() {
+            return Option::Some(Command::press(button, Instant::from_micros(since)))
+        }
+
+        match self.pending[idx] {
+            Option::Some(pending) if since <= pending.until => {
+                self.pending[idx] = Option::None;
+                Option::Some(Command::DoublePress(button))
+            }
+            _ => {
+                let until = now + self.timings.double_press;
+                self.pending[idx]
+                    .replace(Pending { since, until })
+                    .map(|pending| Command::press(button, Instant::from_micros(pending.since)))
+            }
+        }
+    }
+
+    /// Sends a held back press once it can't become a double press, or a
+    /// long press for any button held long enough. A press held back ahead
+    /// of a long one goes first.
+    pub fn poll(&mut self, now: u64) -> Option<Command> {
+        if let Option::Some(button) = Button::ALL
+            .into_iter()
+            .find(|button| self.pending_due(*button).map_or(false, |due| now >= due))
+        {
+            let pending = self.pending[usize::from(button)].take()?;
+
+            return Option::Some(Command::press(button, Instant::from_micros(pending.since)))
+        }
+
+        let button = Button::ALL.into_iter().find(|button| {
+            matches!(
+                self.held[usize::from(*button)],
+                Held::Down { since, used: false } if now.saturating_sub(since) >= self.timings.long_press
+            )
+        })?;
+        self.held[usize::from(button)] = Held::Down {
+            since: now,
+            used: true,
+        };
+
+        Option::Some(Command::LongPress(button))
+    }
+
+    /// When `poll` next has something to do.
+    pub fn deadline(&self) -> Option<u64> {
+        let long_presses = self.held.iter().filter_map(|held| match held {
+            Held::Down { since, used: false } => Option::Some(since + self.timings.long_press),
+            _ => Option::None,
+        });
+        let pending = Button::ALL
+            .into_iter()
+            .filter_map(|button| self.pending_due(button));
+
+        long_presses.chain(pending).min()
+    }
+
+    /// When `button`'s held back press goes out. While a second press that
+    /// came in time is still down it waits on that, until it's a long press.
+    fn pending_due(&self, button: Button) -> Option<u64> {
+        let pending = self.pending[usize::from(button)]?;
+
+        Option::Some(match self.held[usize::from(button)] {
+            Held::Down { since, used: false } if since <= pending.until => {
+                since + self.timings.long_press
+            }
+            _ => pending.until,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG: u64 = 800_000;
+    const DOUBLE: u64 = 300_000;
+
+    fn gestures() -> Gestures {
+        Gestures::new(Timings {
+            long_press: LONG,
+            double_press: DOUBLE,
+        })
+    }
+
+    /// Presses `button` for 50ms at `at`.
+    fn press(gestures: &mut Gestures, button: Button, at: u64) -> Option<Command> {
+        assert!(gestures.down(button, at).is_none());
+        gestures.up(button, at + 50_000)
+    }
+
+    #[test]
+    fn press_without_double_is_sent_on_release() {
+        let mut gestures = gestures();
+
+        let command = press(&mut gestures, Button::Page, 0);
+
+        assert!(matches!(command, Option::Some(Command::PagePress)));
+        assert_eq!(gestures.deadline(), Option::None);
+    }
+
+    #[test]
+    fn bpm_press_carries_when_it_went_down() {
+        let mut gestures = gestures();
+
+        let command = press(&mut gestures, Button::Bpm, 1_000);
+
+        assert!(matches!(
+            command,
+            Option::Some(Command::BpmPress(at)) if at.as_micros() == 1_000
+        ));
+    }
+
+    #[test]
+    fn fast_page_presses_are_all_sent() {
+        let mut gestures = gestures();
+
+        assert!(matches!(
+            press(&mut gestures, Button::Page, 0),
+            Option::Some(Command::PagePress)
+        ));
+        assert!(matches!(
+            press(&mut gestures, Button::Page, 100_000),
+            Option::Some(Command::PagePress)
+        ));
+    }
+
+    #[test]
+    fn encoder_press_waits_out_the_double_press_time() {
+        let mut gestures = gestures();
+
+        assert!(press(&mut gestures, Button::Encoder, 0).is_none());
+        assert_eq!(gestures.deadline(), Option::Some(50_000 + DOUBLE));
+        assert!(gestures.poll(50_000 + DOUBLE - 1).is_none());
+        assert!(matches!(
+            gestures.poll(50_000 + DOUBLE),
+            Option::Some(Command::EncoderPress)
+        ));
+        assert_eq!(gestures.deadline(), Option::None);
+    }
+
+    #[test]
+    fn encoder_double_press_sends_only_the_double() {
+        let mut gestures = gestures();
+
+        assert!(press(&mut gestures, Button::Encoder, 0).is_none());
+        let command = press(&mut gestures, Button::Encoder, 200_000);
+
+        assert!(matches!(
+            command,
+            Option::Some(Command::DoublePress(Button::Encoder))
+        ));
+        assert!(gestures.poll(10_000_000).is_none());
+    }
+
+    #[test]
+    fn slow_encoder_presses_are_two_presses() {
+        let mut gestures = gestures();
+
+        assert!(press(&mut gestures, Button::Encoder, 0).is_none());
+        assert!(matches!(
+            gestures.poll(400_000),
+            Option::Some(Command::EncoderPress)
+        ));
+        assert!(press(&mut gestures, Button::Encoder, 500_000).is_none());
+        assert!(matches!(
+            gestures.poll(900_000),
+            Option::Some(Command::EncoderPress)
+        ));
+    }
+
+    #[test]
+    fn late_poll_still_sends_the_first_press() {
+        let mut gestures = gestures();
+
+        assert!(press(&mut gestures, Button::Encoder, 0).is_none());
+        // Nothing polled, and the second press comes too late for a double.
+        let command = press(&mut gestures, Button::Encoder, 1_000_000);
+
+        assert!(matches!(command, Option::Some(Command::EncoderPress)));
+        assert!(matches!(
+            gestures.poll(1_400_000),
+            Option::Some(Command::EncoderPress)
+        ));
+    }
+
+    #[test]
+    fn long_press_is_sent_while_held() {
+        let mut gestures = gestures();
+
+        assert!(gestures.down(Button::Play, 0).is_none());
+        assert_eq!(gestures.deadline(), Option::Some(LONG));
+        assert!(gestures.poll(LONG - 1).is_none());
+        assert!(matches!(
+            gestures.poll(LONG),
+            Option::Some(Command::LongPress(Button::Play))
+        ));
+        assert!(gestures.up(Button::Play, 2 * LONG).is_none());
+    }
+
+    #[test]
+    fn long_press_is_sent_on_release_if_not_polled() {
+        let mut gestures = gestures();
+
+        assert!(gestures.down(Button::Encoder, 0).is_none());
+        let command = gestures.up(Button::Encoder, LONG);
+
+        assert!(matches!(
+            command,
+            Option::Some(Command::LongPress(Button::Encoder))
+        ));
+        assert!(gestures.poll(10_000_000).is_none());
+    }
+
+    #[test]
+    fn press_then_long_press_sends_both_in_order() {
+        let mut gestures = gestures();
+
+        assert!(press(&mut gestures, Button::Encoder, 0).is_none());
+        assert!(gestures.down(Button::Encoder, 200_000).is_none());
+        // The second press is still down, so the first waits on it.
+        assert!(gestures.poll(400_000).is_none());
+        assert_eq!(gestures.deadline(), Option::Some(200_000 + LONG));
+
+        assert!(matches!(
+            gestures.poll(200_000 + LONG),
+            Option::Some(Command::EncoderPress)
+        ));
+        assert!(matches!(
+            gestures.poll(200_000 + LONG),
+            Option::Some(Command::LongPress(Button::Encoder))
+        ));
+        assert!(gestures.up(Button::Encoder, 2 * LONG).is_none());
+    }
+
+    #[test]
+    fn chord_sends_only_the_chord() {
+        let mut gestures = gestures();
+
+        assert!(gestures.down(Button::Page, 0).is_none());
+        assert!(matches!(
+            gestures.down(Button::Play, 100_000),
+            Option::Some(Command::Chord(Button::Page, Button::Play))
+        ));
+        assert!(gestures.poll(2 * LONG).is_none());
+        assert!(gestures.up(Button::Play, 2 * LONG).is_none());
+        assert!(gestures.up(Button::Page, 2 * LONG).is_none());
+        assert_eq!(gestures.deadline(), Option::None);
+    }
+}
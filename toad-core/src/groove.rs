@@ -0,0 +1,165 @@
+//! Swings outputs by moving their toggles. Each pair of an output's steps
+//! is stretched so the first takes the swing's share of it and the second
+//! the rest, which only ever delays a toggle, by at most half a step at
+//! 75%. A rotated output has its toggles held back whole steps as well,
+//! and is swung where they land. Delayed toggles wait here until their tick
+//! comes round, and never overtake one another.
+//!
+//! The grid is counted in ticks from where the sequences last started, so
+//! a restarted `Sequencer` starts a new `Groove` with them.
+
+use heapless::Deque;
+
+use crate::state::{ratio, StepRate, Swing, MAX_LENGTH};
+
+// Toggles an output can have waiting: two a step, for as many steps as a
+// rotation holds them back, and a step's worth more for the swing.
+const PENDING: usize = 2 * MAX_LENGTH + 2;
+
+/// How an output's toggles are moved.
+#[derive(Clone, Copy)]
+pub struct Timing {
+    pub swing: Swing,
+    /// Ticks a step lasts.
+    pub step: u64,
+    /// Ticks every toggle is held back before it's swung.
+    pub late: u64,
+}
+
+pub struct Groove {
+    tick: u64,
+    pending: [Deque<u64, PENDING>; 4],
+}
+
+impl Default for Groove {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Groove {
+    pub fn new() -> Self {
+        Self {
+            tick: 0,
+            pending: Default::default(),
+        }
+    }
+
+    /// Takes how many times each output toggled on the latest tick and
+    /// returns which to toggle now.
+    pub fn tick(&mut self, toggled: [u8; 4], timings: [Timing; 4]) -> [bool; 4] {
+        let mut toggles = [false; 4];
+
+        for (idx, timing) in timings.into_iter().enumerate() {
+            let pending = &mut self.pending[idx];
+            for _ in 0..toggled[idx] {
+                let late = self.tick + timing.late;
+                let swung = late + delay(late, timing.swing, timing.step);
+                // A tick after the last one waiting at the earliest, so two
+                // toggles can't land together and cancel out.
+                let at = pending.back().map_or(swung, |last| swung.max(last + 1));
+                // Rather toggle early than drop it.
+                if pending.push_back(at).is_err() {
+                    toggles[idx] = !toggles[idx];
+                }
+            }
+            if pending.front().is_some_and(|at| *at <= self.tick) {
+                pending.pop_front();
+                toggles[idx] = !toggles[idx];
+            }
+        }
+        self.tick += 1;
+
+        toggles
+    }
+}
+
+/// How many ticks a step lasts at `rate`.
+pub fn step_ticks(rate: StepRate, resolution: u32) -> u64 {
+    steps_ticks(rate, resolution, 1).max(1)
+}
+
+/// How many ticks `count` steps last at `rate`, rounded down once rather
+/// than a step at a time.
+pub fn steps_ticks(rate: StepRate, resolution: u32, count: u64) -> u64 {
+    let (steps, beats) = ratio(rate);
+
+    resolution as u64 * beats * count / steps
+}
+
+/// How late a toggle `tick`s into the grid lands once swung. The second
+/// step of a pair keeps two ticks, room to rise and fall, so the shortest
+/// steps swing less and two-tick steps not at all.
+pub fn delay(tick: u64, swing: Swing, step: u64) -> u64 {
+    if swing.is_straight() || step == 0 {
+        return 0
+    }
+
+    let most = (step * (2 * swing.0 as u64 - 100) / 100).min(step.saturating_sub(2));
+    let phase = tick % (2 * step);
+    if phase < step {
+        most * phase / step
+    } else {
+        most * (2 * step - phase) / step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRAIGHT: Swing = Swing(50);
+    const MOST: Swing = Swing(75);
+
+    fn timing(swing: Swing, step: u64, late: u64) -> Timing {
+        Timing { swing, step, late }
+    }
+
+    /// Toggles output A on each of `ticks` and returns the ticks it
+    /// toggled on once grooved.
+    fn groove(timing: Timing, ticks: &[u64]) -> heapless::Vec<u64, 8> {
+        let mut groove = Groove::new();
+        let timings = [timing, timing, timing, timing];
+
+        (0..64)
+            .filter(|tick| {
+                let toggled = [ticks.contains(tick) as u8, 0, 0, 0];
+                groove.tick(toggled, timings)[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delays_the_second_step_of_each_pair() {
+        // At 75% a 24 tick step swings up to 12 ticks, at 60% up to 4.
+        let delays = [0, 12, 24, 36, 48].map(|tick| delay(tick, MOST, 24));
+        assert_eq!(delays, [0, 6, 12, 6, 0]);
+        assert_eq!(delay(24, Swing(60), 24), 4);
+        assert_eq!(delay(24, STRAIGHT, 24), 0);
+    }
+
+    #[test]
+    fn leaves_room_to_rise_and_fall_in_short_steps() {
+        assert_eq!(delay(4, MOST, 4), 2);
+        assert_eq!(delay(3, MOST, 3), 1);
+        assert_eq!(delay(2, MOST, 2), 0);
+        assert_eq!(delay(1, MOST, 1), 0);
+    }
+
+    #[test]
+    fn moves_toggles_to_their_swung_tick() {
+        // Rising and falling on each of the first two 4 tick steps.
+        let toggles = [0, 2, 4, 6];
+
+        assert_eq!(groove(timing(STRAIGHT, 4, 0), &toggles), [0, 2, 4, 6]);
+        assert_eq!(groove(timing(MOST, 4, 0), &toggles), [0, 3, 6, 7]);
+        assert_eq!(groove(timing(MOST, 4, 4), &toggles), [6, 7, 8, 11]);
+        assert_eq!(groove(timing(MOST, 2, 0), &[0, 1, 2, 3]), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn never_lets_toggles_overtake() {
+        // Tick 4 lands on 6 and tick 5 would too.
+        assert_eq!(groove(timing(MOST, 4, 0), &[4, 5]), [6, 7]);
+    }
+}
@@ -0,0 +1,15 @@
+#![no_std]
+#![allow(incomplete_features)]
+#![feature(async_fn_in_trait)]
+
+pub mod animator;
+pub mod clock;
+pub mod display;
+pub mod euclid;
+pub mod gestures;
+pub mod groove;
+pub mod screens;
+pub mod sequencer;
+pub mod state;
+pub mod state_memo;
+pub mod storage;
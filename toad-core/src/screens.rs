@@ -1,34 +1,43 @@
 use seq::OutputType;
 
 use crate::{
-    display::Display,
-    screens::{euclid::EuclidScreen, gate::GateScreen, home::HomeScreen},
-    state::Screen,
-    StateChange,
+    display::{Canvas, Display},
+    screens::{euclid::EuclidScreen, gate::GateScreen, home::HomeScreen, presets::PresetsScreen},
+    state::{Screen, StateChange},
 };
 
 mod euclid;
 mod gate;
 mod home;
+mod presets;
+mod rate_label;
 
 pub struct Screens {
     euclid: EuclidScreen,
     gate: GateScreen,
     home: HomeScreen,
+    presets: PresetsScreen,
     current_screen: Screen,
 }
 
+impl Default for Screens {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Screens {
     pub fn new() -> Self {
         Self {
             euclid: EuclidScreen::new(),
             gate: GateScreen::new(),
             home: HomeScreen::new(),
+            presets: PresetsScreen::new(),
             current_screen: Screen::Home,
         }
     }
 
-    pub fn draw(&mut self, state_change: StateChange, display: &mut Display) {
+    pub fn draw<D: Canvas>(&mut self, state_change: StateChange, display: &mut Display<D>) {
         if let Some(screen) = Option::<Screen>::from(&state_change) {
             self.current_screen = screen;
         };
@@ -39,6 +48,7 @@ impl Screens {
                 OutputType::Gate => self.gate.draw(state_change, display),
                 OutputType::Euclid => self.euclid.draw(state_change, display),
             },
+            Screen::Presets => self.presets.draw(state_change, display),
         }
     }
 }
@@ -0,0 +1,295 @@
+use embedded_graphics::prelude::Point;
+use heapless::{String, Vec};
+use seq::{Density, Length, OutputType};
+
+use crate::{
+    display::{Canvas, Display},
+    euclid::euclid,
+    screens::rate_label::RateLabel,
+    state::{
+        EditMode, Element, Output, OutputScreenState, OutputTypeString, Rotation, ScreenState,
+        SequenceState, StateChange, Swing, SwingString, MAX_LENGTH,
+    },
+};
+
+const GRID_START_X: usize = 54;
+const GRID_START_Y: usize = 46;
+// The grid shows one page of steps at a time, the one that's playing.
+const PAGE_STEPS: usize = 16;
+
+pub struct EuclidScreen {
+    edit_mode_str: String<4>,
+    length_str: String<3>,
+    name_str: String<3>,
+    output_type_str: String<3>,
+    page: usize,
+    page_str: String<5>,
+    rate: RateLabel,
+    rotation_str: String<3>,
+    sequence: Vec<bool, MAX_LENGTH>,
+    swing_str: String<3>,
+}
+
+impl EuclidScreen {
+    pub fn new() -> Self {
+        Self {
+            edit_mode_str: String::new(),
+            length_str: String::new(),
+            name_str: String::new(),
+            output_type_str: String::new(),
+            page: 0,
+            page_str: String::new(),
+            rate: RateLabel::new(),
+            rotation_str: String::new(),
+            sequence: Vec::new(),
+            swing_str: String::new(),
+        }
+    }
+
+    pub fn draw<D: Canvas>(&mut self, state_change: StateChange, display: &mut Display<D>) {
+        match state_change {
+            StateChange::Rate(.., rate) => {
+                self.rate.clear(display);
+                self.rate.draw(display, rate);
+            }
+            StateChange::Sequence(SequenceState {
+                length,
+                density,
+                rotation,
+                index,
+                ..
+            }) => {
+                let index = index.unwrap_or(0);
+                self.update_sequence(length, density, rotation);
+                self.page = index / PAGE_STEPS;
+                self.clear_length(display);
+                self.draw_length(display, length);
+                self.clear_rotation(display);
+                self.draw_rotation(display, rotation);
+                self.redraw_page(display);
+                self.draw_caret(display, index)
+            }
+            StateChange::Swing(_, swing) => {
+                self.clear_swing(display);
+                self.draw_swing(display, swing);
+            }
+            StateChange::EditMode(_, edit_mode) => {
+                self.clear_edit_mode(display);
+                self.draw_edit_mode(display, edit_mode);
+            }
+            StateChange::OutputType(screen_state) => {
+                self.redraw_screen(display, screen_state, Element::OutputType);
+            }
+            StateChange::Index(_, index) => self.draw_caret(display, index),
+            StateChange::NextElement(_, previous_element, current_element) => {
+                self.clear_pointer(display, previous_element);
+                self.rate.select(display, current_element.clone());
+                self.draw_pointer(display, current_element);
+            }
+            StateChange::NextScreen(screen_state) => {
+                self.redraw_screen(display, screen_state, Element::Rate);
+            }
+            _ => {}
+        }
+    }
+
+    fn redraw_screen<D: Canvas>(
+        &mut self,
+        display: &mut Display<D>,
+        screen_state: ScreenState,
+        element: Element,
+    ) {
+        if let ScreenState::Output(OutputScreenState {
+            output,
+            config,
+            rate,
+            rotation,
+            swing,
+            edit_mode,
+            index,
+        }) = screen_state
+        {
+            display.clear();
+            self.update_sequence(config.length(), config.density(), rotation);
+            self.page = index.unwrap_or(0) / PAGE_STEPS;
+            self.draw_name(display, output);
+            self.draw_clock(display);
+            self.rate.draw(display, rate);
+            self.rate.select(display, element.clone());
+            self.draw_length(display, config.length());
+            self.draw_rotation(display, rotation);
+            self.draw_grid(display);
+            self.draw_page(display);
+            self.draw_caret(display, index.unwrap_or(0));
+            self.draw_swing(display, swing);
+            self.draw_output_type(display, config.output_type());
+            self.draw_edit_mode(display, edit_mode);
+            self.draw_pointer(display, element);
+        }
+    }
+
+    fn draw_name<D: Canvas>(&mut self, display: &mut Display<D>, output: Output) {
+        display.draw_bigge_text(&mut self.name_str, output, Point::new(0, 24));
+    }
+
+    fn draw_clock<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.draw_clock(Point::new(54, 8));
+    }
+
+    fn clear_length<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.length_str, Point::new(74, 45));
+    }
+
+    fn draw_length<D: Canvas>(&mut self, display: &mut Display<D>, length: Length) {
+        display.draw_smol_text(&mut self.length_str, length.0, Point::new(74, 45));
+    }
+
+    fn clear_rotation<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.rotation_str, Point::new(96, 45));
+    }
+
+    fn draw_rotation<D: Canvas>(&mut self, display: &mut Display<D>, rotation: Rotation) {
+        display.draw_smol_text(&mut self.rotation_str, rotation, Point::new(96, 45));
+    }
+
+    #[inline(always)]
+    fn caret_point(&self, idx: usize) -> Point {
+        let mut grid_point = self.grid_point(idx);
+        grid_point.x += 1;
+        grid_point.y -= 3;
+        grid_point
+    }
+
+    #[inline(always)]
+    fn grid_point(&self, idx: usize) -> Point {
+        let x = idx % 8;
+        let y = idx / 8;
+        let p_x = GRID_START_X + x * (5 + 2);
+        let p_y = GRID_START_Y + y * (5 + 5);
+        Point::new(p_x as i32, p_y as i32)
+    }
+
+    fn clear_grid<D: Canvas>(&mut self, display: &mut Display<D>) {
+        for idx in 0..PAGE_STEPS {
+            display.clear_step_on(self.grid_point(idx));
+        }
+    }
+
+    fn draw_grid<D: Canvas>(&mut self, display: &mut Display<D>) {
+        let start = self.page * PAGE_STEPS;
+        let end = self.sequence.len().min(start + PAGE_STEPS);
+        for idx in start..end {
+            let step_on = self.sequence[idx];
+            let point = self.grid_point(idx % PAGE_STEPS);
+            if step_on {
+                display.draw_step_on(point);
+            } else {
+                display.draw_step_off(point);
+            };
+        }
+    }
+
+    fn clear_page<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.page_str, Point::new(110, 61));
+    }
+
+    /// Which page is showing, when there's more than one.
+    fn draw_page<D: Canvas>(&mut self, display: &mut Display<D>) {
+        let pages = self.sequence.len().div_ceil(PAGE_STEPS);
+        self.page_str.clear();
+        if pages > 1 {
+            let page = self.page + 1;
+            let point = Point::new(110, 61);
+            display.draw_smol_text(&mut self.page_str, format_args!("{page}/{pages}"), point);
+        }
+    }
+
+    fn redraw_page<D: Canvas>(&mut self, display: &mut Display<D>) {
+        self.clear_grid(display);
+        self.draw_grid(display);
+        self.clear_carets(display);
+        self.clear_page(display);
+        self.draw_page(display);
+    }
+
+    pub fn clear_carets<D: Canvas>(&mut self, display: &mut Display<D>) {
+        for idx in 0..PAGE_STEPS {
+            display.clear_caret(self.caret_point(idx));
+        }
+    }
+
+    /// Moves the caret to `index`, turning the page when it plays off the
+    /// end of this one.
+    pub fn draw_caret<D: Canvas>(&mut self, display: &mut Display<D>, index: usize) {
+        let page = index / PAGE_STEPS;
+        if page != self.page {
+            self.page = page;
+            self.redraw_page(display);
+        } else {
+            let len = self.sequence.len().max(1);
+            let prev = (index + len - 1) % len;
+            display.clear_caret(self.caret_point(prev % PAGE_STEPS));
+        }
+        display.draw_caret(self.caret_point(index % PAGE_STEPS));
+    }
+
+    fn clear_swing<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.swing_str, Point::new(96, 29));
+    }
+
+    fn draw_swing<D: Canvas>(&mut self, display: &mut Display<D>, swing: Option<Swing>) {
+        let str = SwingString::from(swing).0;
+        display.draw_smol_text(&mut self.swing_str, str, Point::new(96, 29));
+    }
+
+    fn clear_edit_mode<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.edit_mode_str, Point::new(96, 12));
+    }
+
+    /// Blank in normal editing.
+    fn draw_edit_mode<D: Canvas>(&mut self, display: &mut Display<D>, edit_mode: EditMode) {
+        let label = match edit_mode {
+            EditMode::Normal => "",
+            EditMode::Fine => "Fine",
+            EditMode::Coarse => "x10",
+        };
+        display.draw_smol_text(&mut self.edit_mode_str, label, Point::new(96, 12));
+    }
+
+    fn draw_output_type<D: Canvas>(&mut self, display: &mut Display<D>, output_type: OutputType) {
+        let str = OutputTypeString::from(output_type).0;
+        display.draw_bigge_text(&mut self.output_type_str, str, Point::new(0, 50));
+    }
+
+    fn clear_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Rate | Element::Beats => display.clear_pointer_right(Point::new(36, 10)),
+            Element::Length => display.clear_pointer_right(Point::new(36, 28)),
+            Element::Density => display.clear_pointer_right(Point::new(36, 46)),
+            Element::Rotation => display.clear_pointer_left(Point::new(113, 36)),
+            Element::Swing => display.clear_pointer_left(Point::new(113, 20)),
+            Element::OutputType => display.clear_pointer_left(Point::new(20, 25)),
+            _ => {}
+        };
+    }
+
+    fn draw_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Rate | Element::Beats => display.draw_pointer_right(Point::new(36, 10)),
+            Element::Length => display.draw_pointer_right(Point::new(36, 28)),
+            Element::Density => display.draw_pointer_right(Point::new(36, 46)),
+            Element::Rotation => display.draw_pointer_left(Point::new(113, 36)),
+            Element::Swing => display.draw_pointer_left(Point::new(113, 20)),
+            Element::OutputType => display.draw_pointer_left(Point::new(20, 25)),
+            _ => {}
+        };
+    }
+
+    fn update_sequence(&mut self, length: Length, density: Density, rotation: Rotation) {
+        euclid(density, length, &mut self.sequence);
+        let rotation = rotation.0 as usize;
+        if rotation < self.sequence.len() {
+            self.sequence.rotate_right(rotation);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use embedded_graphics::prelude::Point;
+use heapless::String;
+use seq::{OutputType, Prob, Pwm};
+
+use crate::{
+    display::{Canvas, Display},
+    screens::rate_label::RateLabel,
+    state::{
+        EditMode, Element, Output, OutputScreenState, OutputTypeString, ProbString, ScreenState,
+        StateChange, Swing, SwingString,
+    },
+};
+
+pub struct GateScreen {
+    edit_mode_str: String<4>,
+    name_str: String<3>,
+    output_type_str: String<3>,
+    prob_str: String<4>,
+    rate: RateLabel,
+    swing_str: String<3>,
+}
+
+impl GateScreen {
+    pub fn new() -> Self {
+        Self {
+            edit_mode_str: String::new(),
+            name_str: String::new(),
+            output_type_str: String::new(),
+            prob_str: String::new(),
+            rate: RateLabel::new(),
+            swing_str: String::new(),
+        }
+    }
+
+    pub fn draw<D: Canvas>(&mut self, state_change: StateChange, display: &mut Display<D>) {
+        match state_change {
+            StateChange::Rate(.., rate) => {
+                self.rate.clear(display);
+                self.rate.draw(display, rate);
+            }
+            StateChange::Prob(_, prob) => {
+                self.clear_prob(display);
+                self.draw_prob(display, prob);
+            }
+            StateChange::Pwm(_, pwm) => {
+                self.draw_pwm(display, pwm);
+            }
+            StateChange::Swing(_, swing) => {
+                self.clear_swing(display);
+                self.draw_swing(display, swing);
+            }
+            StateChange::EditMode(_, edit_mode) => {
+                self.clear_edit_mode(display);
+                self.draw_edit_mode(display, edit_mode);
+            }
+            StateChange::OutputType(screen_state) => {
+                self.redraw_screen(display, screen_state, Element::OutputType);
+            }
+            StateChange::NextElement(_, previous_element, current_element) => {
+                self.clear_pointer(display, previous_element);
+                self.rate.select(display, current_element.clone());
+                self.draw_pointer(display, current_element);
+            }
+            StateChange::NextScreen(screen_state) => {
+                self.redraw_screen(display, screen_state, Element::Rate);
+            }
+            _ => {}
+        }
+    }
+
+    fn redraw_screen<D: Canvas>(
+        &mut self,
+        display: &mut Display<D>,
+        screen_state: ScreenState,
+        element: Element,
+    ) {
+        if let ScreenState::Output(OutputScreenState {
+            output,
+            config,
+            rate,
+            swing,
+            edit_mode,
+            ..
+        }) = screen_state
+        {
+            display.clear();
+            self.draw_name(display, output);
+            self.draw_clock(display);
+            self.draw_dice(display);
+            self.rate.draw(display, rate);
+            self.rate.select(display, element.clone());
+            self.draw_prob(display, config.prob());
+            self.draw_pwm(display, config.pwm()); // 65x16 (13x8)
+            self.draw_swing(display, swing);
+            self.draw_output_type(display, config.output_type());
+            self.draw_edit_mode(display, edit_mode);
+            self.draw_pointer(display, element);
+        }
+    }
+
+    fn draw_name<D: Canvas>(&mut self, display: &mut Display<D>, output: Output) {
+        display.draw_bigge_text(&mut self.name_str, output, Point::new(0, 24));
+    }
+
+    fn draw_clock<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.draw_clock(Point::new(54, 8));
+    }
+
+    fn draw_dice<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.draw_dice(Point::new(54, 26));
+    }
+
+    fn clear_prob<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.prob_str, Point::new(74, 46));
+    }
+
+    fn draw_prob<D: Canvas>(&mut self, display: &mut Display<D>, prob: Prob) {
+        let str = ProbString::from(prob).0;
+        display.draw_smol_text(&mut self.prob_str, str, Point::new(74, 46));
+    }
+
+    fn draw_pwm<D: Canvas>(&mut self, display: &mut Display<D>, pwm: Pwm) {
+        let point = Point::new(55, 46);
+        display.clear_pwm(point);
+        display.draw_pwm(pwm.index(), point);
+    }
+
+    fn clear_swing<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.swing_str, Point::new(96, 29));
+    }
+
+    fn draw_swing<D: Canvas>(&mut self, display: &mut Display<D>, swing: Option<Swing>) {
+        let str = SwingString::from(swing).0;
+        display.draw_smol_text(&mut self.swing_str, str, Point::new(96, 29));
+    }
+
+    fn clear_edit_mode<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.edit_mode_str, Point::new(96, 12));
+    }
+
+    /// Blank in normal editing.
+    fn draw_edit_mode<D: Canvas>(&mut self, display: &mut Display<D>, edit_mode: EditMode) {
+        let label = match edit_mode {
+            EditMode::Normal => "",
+            EditMode::Fine => "Fine",
+            EditMode::Coarse => "x10",
+        };
+        display.draw_smol_text(&mut self.edit_mode_str, label, Point::new(96, 12));
+    }
+
+    fn draw_output_type<D: Canvas>(&mut self, display: &mut Display<D>, output_type: OutputType) {
+        let str = OutputTypeString::from(output_type).0;
+        display.draw_bigge_text(&mut self.output_type_str, str, Point::new(0, 50));
+    }
+
+    fn clear_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Rate | Element::Beats => display.clear_pointer_right(Point::new(36, 10)),
+            Element::Prob => display.clear_pointer_right(Point::new(36, 28)),
+            Element::Pwm => display.clear_pointer_right(Point::new(36, 46)),
+            Element::Swing => display.clear_pointer_left(Point::new(113, 20)),
+            Element::OutputType => display.clear_pointer_left(Point::new(20, 25)),
+            _ => {}
+        };
+    }
+
+    fn draw_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Rate | Element::Beats => display.draw_pointer_right(Point::new(36, 10)),
+            Element::Prob => display.draw_pointer_right(Point::new(36, 28)),
+            Element::Pwm => display.draw_pointer_right(Point::new(36, 46)),
+            Element::Swing => display.draw_pointer_left(Point::new(113, 20)),
+            Element::OutputType => display.draw_pointer_left(Point::new(20, 25)),
+            _ => {}
+        };
+    }
+}
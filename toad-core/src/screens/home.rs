@@ -0,0 +1,209 @@
+use embedded_graphics::prelude::Point;
+use heapless::String;
+
+use crate::{
+    display::{Canvas, Display},
+    state::{
+        Bpm, EditMode, Element, HomeScreenState, Lock, PlayStatus, Ppqn, ScreenState, StateChange,
+        Swing, Sync,
+    },
+};
+
+pub struct HomeScreen {
+    bpm_label: String<4>,
+    bpm_str: String<5>,
+    frogge_index: usize,
+    lock_str: String<4>,
+    ppqn_str: String<2>,
+    swing_str: String<3>,
+    sync_str: String<3>,
+}
+
+impl Default for HomeScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HomeScreen {
+    pub fn new() -> Self {
+        let bpm_label = String::new();
+        let bpm_str = String::new();
+        let ppqn_str = String::new();
+        let sync_str = String::new();
+
+        Self {
+            bpm_label,
+            bpm_str,
+            frogge_index: 0,
+            lock_str: String::new(),
+            ppqn_str,
+            swing_str: String::new(),
+            sync_str,
+        }
+    }
+
+    pub fn draw<D: Canvas>(&mut self, state_change: StateChange, display: &mut Display<D>) {
+        match state_change {
+            StateChange::Frame => self.draw_frogge(display),
+            StateChange::Bpm(bpm) => {
+                self.clear_bpm_value(display);
+                self.draw_bpm_value(display, bpm);
+            }
+            StateChange::Downbeat(preset, _) => {
+                self.clear_bpm_value(display);
+                self.draw_bpm_value(display, preset.bpm);
+            }
+            StateChange::EditMode(_, edit_mode) => {
+                self.clear_bpm_label(display);
+                self.draw_bpm_label(display, edit_mode);
+            }
+            StateChange::Sync(sync) => {
+                self.clear_sync(display);
+                self.draw_sync(display, sync);
+                self.clear_lock(display);
+            }
+            StateChange::Lock(lock) => {
+                self.clear_lock(display);
+                self.draw_lock(display, lock);
+            }
+            StateChange::Ppqn(ppqn) => {
+                self.clear_ppqn(display);
+                self.draw_ppqn(display, ppqn);
+            }
+            StateChange::Swing(_, Option::Some(swing)) => {
+                self.clear_swing(display);
+                self.draw_swing(display, swing);
+            }
+            StateChange::PlayStatus(_, play_status) => self.draw_play_pause(display, play_status),
+            StateChange::Stop(..) => self.draw_play_pause(display, PlayStatus::Stopped),
+            StateChange::NextElement(_, previous_element, current_element) => {
+                self.clear_pointer(display, previous_element);
+                self.draw_pointer(display, current_element);
+            }
+            StateChange::NextScreen(screen_state) => {
+                self.redraw_screen(display, screen_state, Element::Bpm);
+            }
+            _ => {}
+        }
+    }
+
+    fn redraw_screen<D: Canvas>(
+        &mut self,
+        display: &mut Display<D>,
+        screen_state: ScreenState,
+        element: Element,
+    ) {
+        if let ScreenState::Home(HomeScreenState {
+            bpm,
+            sync,
+            ppqn,
+            swing,
+            play_status,
+        }) = screen_state
+        {
+            display.clear();
+            self.draw_bpm_label(display, EditMode::Normal);
+            self.draw_bpm_value(display, bpm);
+            self.draw_frogge(display);
+            self.draw_sync(display, sync);
+            self.draw_ppqn(display, ppqn);
+            self.draw_swing(display, swing);
+            self.draw_play_pause(display, play_status);
+            self.draw_pointer(display, element);
+        }
+    }
+
+    fn clear_bpm_label<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.bpm_label, Point::new(68, 27));
+    }
+
+    fn draw_bpm_label<D: Canvas>(&mut self, display: &mut Display<D>, edit_mode: EditMode) {
+        let label = match edit_mode {
+            EditMode::Normal => "BPM",
+            EditMode::Fine => "Fine",
+            EditMode::Coarse => "x10",
+        };
+        display.draw_smol_text(&mut self.bpm_label, label, Point::new(68, 27));
+    }
+
+    fn clear_bpm_value<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_bigge_text(&self.bpm_str, Point::new(22, 30));
+    }
+
+    fn draw_bpm_value<D: Canvas>(&mut self, display: &mut Display<D>, bpm: Bpm) {
+        display.draw_bigge_text(&mut self.bpm_str, bpm, Point::new(22, 30));
+    }
+
+    fn draw_frogge<D: Canvas>(&mut self, display: &mut Display<D>) {
+        let point = Point::new(80, 26);
+        display.clear_frogge(point);
+        display.draw_frogge(self.frogge_index, point);
+        self.frogge_index += 1;
+        self.frogge_index %= 8;
+    }
+
+    fn clear_sync<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.sync_str, Point::new(22, 50));
+    }
+
+    fn draw_sync<D: Canvas>(&mut self, display: &mut Display<D>, sync: Sync) {
+        display.draw_smol_text(&mut self.sync_str, sync, Point::new(22, 50));
+    }
+
+    /// Only drawn in `Sync::Ext`, and by the tick task as it's the one
+    /// following the clock.
+    fn clear_lock<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.lock_str, Point::new(22, 61));
+    }
+
+    fn draw_lock<D: Canvas>(&mut self, display: &mut Display<D>, lock: Lock) {
+        display.draw_smol_text(&mut self.lock_str, lock, Point::new(22, 61));
+    }
+
+    fn clear_ppqn<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.ppqn_str, Point::new(104, 22));
+    }
+
+    fn draw_ppqn<D: Canvas>(&mut self, display: &mut Display<D>, ppqn: Ppqn) {
+        display.draw_smol_text(&mut self.ppqn_str, ppqn, Point::new(104, 22));
+    }
+
+    fn clear_swing<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.swing_str, Point::new(96, 61));
+    }
+
+    fn draw_swing<D: Canvas>(&mut self, display: &mut Display<D>, swing: Swing) {
+        display.draw_smol_text(&mut self.swing_str, swing, Point::new(96, 61));
+    }
+
+    fn draw_play_pause<D: Canvas>(&mut self, display: &mut Display<D>, play_status: PlayStatus) {
+        let point = Point::new(56, 30);
+        let index = match play_status {
+            PlayStatus::Playing => 0,
+            PlayStatus::Paused | PlayStatus::Stopped => 1,
+        };
+        display.clear_play_pause(point);
+        display.draw_play_pause(index, point);
+    }
+
+    fn clear_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Bpm => display.clear_pointer_right(Point::new(4, 8)),
+            Element::Sync => display.clear_pointer_right(Point::new(4, 32)),
+            Element::Ppqn => display.clear_pointer_right(Point::new(86, 4)),
+            Element::Swing => display.clear_pointer_left(Point::new(113, 52)),
+            _ => {}
+        };
+    }
+
+    fn draw_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Bpm => display.draw_pointer_right(Point::new(4, 8)),
+            Element::Sync => display.draw_pointer_right(Point::new(4, 32)),
+            Element::Ppqn => display.draw_pointer_right(Point::new(86, 4)),
+            Element::Swing => display.draw_pointer_left(Point::new(113, 52)),
+            _ => {}
+        };
+    }
+}
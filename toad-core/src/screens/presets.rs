@@ -0,0 +1,164 @@
+use embedded_graphics::prelude::Point;
+use heapless::String;
+
+use crate::{
+    display::{Canvas, Display},
+    state::{Element, Name, PresetsScreenState, ScreenState, Slot, StateChange, NAME_LENGTH},
+};
+
+pub struct PresetsScreen {
+    load_label: String<4>,
+    save_label: String<4>,
+    slot_str: String<3>,
+    name_str: String<NAME_LENGTH>,
+    name_char: Option<usize>,
+    used_str: String<5>,
+}
+
+impl PresetsScreen {
+    pub fn new() -> Self {
+        Self {
+            load_label: String::new(),
+            save_label: String::new(),
+            slot_str: String::new(),
+            name_str: String::new(),
+            name_char: Option::None,
+            used_str: String::new(),
+        }
+    }
+
+    pub fn draw<D: Canvas>(&mut self, state_change: StateChange, display: &mut Display<D>) {
+        match state_change {
+            StateChange::Slot(PresetsScreenState { slot, name, used }) => {
+                self.clear_slot(display);
+                self.draw_slot(display, slot);
+                self.clear_name(display);
+                self.draw_name(display, name);
+                self.clear_used(display);
+                self.draw_used(display, used);
+                self.back_to_slot(display);
+            }
+            StateChange::Preset(preset) => {
+                self.clear_name(display);
+                self.draw_name(display, preset.name);
+                self.clear_used(display);
+                self.draw_used(display, true);
+                self.back_to_slot(display);
+            }
+            StateChange::Name(name, at) => {
+                self.clear_caret(display);
+                self.clear_name(display);
+                self.draw_name(display, name);
+                self.draw_caret(display, at);
+            }
+            StateChange::NextElement(_, previous_element, current_element) => {
+                self.clear_pointer(display, previous_element.clone());
+                self.draw_pointer(display, current_element.clone());
+                if let Element::Name = previous_element {
+                    self.clear_caret(display);
+                }
+                if let Element::Name = current_element {
+                    self.draw_caret(display, 0);
+                }
+            }
+            StateChange::NextScreen(screen_state) => {
+                self.redraw_screen(display, screen_state, Element::Slot);
+            }
+            _ => {}
+        }
+    }
+
+    fn redraw_screen<D: Canvas>(
+        &mut self,
+        display: &mut Display<D>,
+        screen_state: ScreenState,
+        element: Element,
+    ) {
+        if let ScreenState::Presets(PresetsScreenState { slot, name, used }) = screen_state {
+            display.clear();
+            self.name_char = Option::None;
+            self.draw_slot(display, slot);
+            self.draw_name(display, name);
+            self.draw_used(display, used);
+            self.draw_load_label(display);
+            self.draw_save_label(display);
+            self.draw_pointer(display, element);
+        }
+    }
+
+    fn clear_slot<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_bigge_text(&self.slot_str, Point::new(22, 30));
+    }
+
+    fn draw_slot<D: Canvas>(&mut self, display: &mut Display<D>, slot: Slot) {
+        display.draw_bigge_text(&mut self.slot_str, slot, Point::new(22, 30));
+    }
+
+    fn clear_name<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.name_str, Point::new(22, 50));
+    }
+
+    fn draw_name<D: Canvas>(&mut self, display: &mut Display<D>, name: Name) {
+        display.draw_smol_text(&mut self.name_str, name, Point::new(22, 50));
+    }
+
+    fn caret_point<D: Canvas>(&self, display: &Display<D>, at: usize) -> Point {
+        let before = display.smol_text_width(&self.name_str[..at]);
+        Point::new(23 + before as i32, 40)
+    }
+
+    fn clear_caret<D: Canvas>(&mut self, display: &mut Display<D>) {
+        if let Option::Some(at) = self.name_char.take() {
+            display.clear_caret(self.caret_point(display, at));
+        }
+    }
+
+    fn draw_caret<D: Canvas>(&mut self, display: &mut Display<D>, at: usize) {
+        display.draw_caret(self.caret_point(display, at));
+        self.name_char = Option::Some(at);
+    }
+
+    /// Pressing load or save puts the pointer back on the slot.
+    fn back_to_slot<D: Canvas>(&mut self, display: &mut Display<D>) {
+        self.clear_pointer(display, Element::Load);
+        self.clear_pointer(display, Element::Save);
+        self.draw_pointer(display, Element::Slot);
+    }
+
+    fn clear_used<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.used_str, Point::new(22, 61));
+    }
+
+    fn draw_used<D: Canvas>(&mut self, display: &mut Display<D>, used: bool) {
+        let str = if used { "Saved" } else { "Empty" };
+        display.draw_smol_text(&mut self.used_str, str, Point::new(22, 61));
+    }
+
+    fn draw_load_label<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.draw_smol_text(&mut self.load_label, "Load", Point::new(92, 29));
+    }
+
+    fn draw_save_label<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.draw_smol_text(&mut self.save_label, "Save", Point::new(92, 46));
+    }
+
+    fn clear_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Slot => display.clear_pointer_right(Point::new(4, 8)),
+            Element::Name => display.clear_pointer_right(Point::new(4, 32)),
+            Element::Load => display.clear_pointer_right(Point::new(56, 10)),
+            Element::Save => display.clear_pointer_right(Point::new(56, 27)),
+            _ => {}
+        };
+    }
+
+    fn draw_pointer<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        match element {
+            Element::Slot => display.draw_pointer_right(Point::new(4, 8)),
+            Element::Name => display.draw_pointer_right(Point::new(4, 32)),
+            Element::Load => display.draw_pointer_right(Point::new(56, 10)),
+            Element::Save => display.draw_pointer_right(Point::new(56, 27)),
+            _ => {}
+        };
+    }
+}
@@ -0,0 +1,73 @@
+use embedded_graphics::prelude::Point;
+use heapless::String;
+use seq::Rate;
+
+use crate::{
+    display::{Canvas, Display},
+    state::{Element, RateString, StepRate},
+};
+
+const POINT: Point = Point::new(72, 29);
+
+/// The rate on the gate and euclid screens. A ratio gets a caret under
+/// whichever side the encoder is turning.
+pub struct RateLabel {
+    rate: StepRate,
+    rate_str: String<5>,
+    caret: Option<Point>,
+    element: Element,
+}
+
+impl RateLabel {
+    pub fn new() -> Self {
+        Self {
+            rate: StepRate::Seq(Rate::Unity),
+            rate_str: String::new(),
+            caret: Option::None,
+            element: Element::Rate,
+        }
+    }
+
+    pub fn clear<D: Canvas>(&mut self, display: &mut Display<D>) {
+        display.clear_smol_text(&self.rate_str, POINT);
+        self.clear_caret(display);
+    }
+
+    pub fn draw<D: Canvas>(&mut self, display: &mut Display<D>, rate: StepRate) {
+        self.rate = rate;
+        let str = RateString::from(rate).0;
+        display.draw_smol_text(&mut self.rate_str, str, POINT);
+        self.draw_caret(display);
+    }
+
+    /// Follows the selected element.
+    pub fn select<D: Canvas>(&mut self, display: &mut Display<D>, element: Element) {
+        self.element = element;
+        self.clear_caret(display);
+        self.draw_caret(display);
+    }
+
+    fn clear_caret<D: Canvas>(&mut self, display: &mut Display<D>) {
+        if let Option::Some(point) = self.caret.take() {
+            display.clear_caret(point);
+        }
+    }
+
+    fn draw_caret<D: Canvas>(&mut self, display: &mut Display<D>) {
+        let Option::Some((steps, beats)) = self.rate_str.split_once('/') else {
+            return
+        };
+        let (start, part) = match (self.rate, &self.element) {
+            (StepRate::Ratio(..), Element::Rate) => (0, steps),
+            (StepRate::Ratio(..), Element::Beats) => (
+                display.smol_text_width(&self.rate_str[..=steps.len()]),
+                beats,
+            ),
+            _ => return,
+        };
+        let x = POINT.x + (start + display.smol_text_width(part) / 2) as i32 - 1;
+        let point = Point::new(x, POINT.y + 2);
+        display.draw_caret(point);
+        self.caret = Option::Some(point);
+    }
+}
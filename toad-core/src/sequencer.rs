@@ -0,0 +1,346 @@
+//! Plays the outputs. `Seq` steps them, and what it has no setting for is
+//! done here on top of it.
+//!
+//! An output on a ratio or a dotted or triplet note gets a `Seq` of its own
+//! at one step a beat, ticked that many steps for every so many beats of
+//! ticks. It starts from its first step whenever it's put on one.
+//!
+//! `Seq` plays every output as a gate, and a euclid output lets through only
+//! the toggles of the steps its pattern has on, so patterns can be longer
+//! than `Seq`'s. It counts the output's steps from where it was last put on
+//! its first.
+//!
+//! Rotation holds a euclid output's toggles back by whole steps, which
+//! plays the pattern rotated right against the index `Seq` reports, the
+//! same way the screen draws it. Until a rotated output has played that
+//! many steps after starting over it stays quiet, as there's nothing
+//! earlier to bring round.
+
+use heapless::Vec;
+use seq::{Density, Length, OutputConfig, OutputType, Prob, Pwm, Seq};
+
+use crate::{
+    euclid::euclid,
+    groove::{self, Groove, Timing},
+    state::{Preset, Rotation, StepRate, Swing, MAX_LENGTH},
+};
+
+pub struct Sequencer {
+    seq: Seq,
+    scaled: [Option<Scaled>; 4],
+    patterns: [Pattern; 4],
+    groove: Groove,
+    bpm: u32,
+    configs: Vec<OutputConfig, 4>,
+    rates: [StepRate; 4],
+    rotations: [Rotation; 4],
+    swing: Swing,
+    swings: [Option<Swing>; 4],
+}
+
+impl Sequencer {
+    /// Plays `preset` from the top.
+    pub fn new(preset: &Preset) -> Self {
+        let bpm = preset.bpm.whole();
+        let played = preset.outputs.iter().map(played).collect();
+        let scaled = core::array::from_fn(|idx| {
+            match (preset.rates[idx].scaled(), preset.outputs.get(idx)) {
+                (Option::Some((steps, beats)), Option::Some(config)) => {
+                    Option::Some(Scaled::new(bpm, config, steps, beats))
+                }
+                _ => Option::None,
+            }
+        });
+
+        let patterns = core::array::from_fn(|idx| {
+            preset
+                .outputs
+                .get(idx)
+                .map_or_else(Pattern::default, Pattern::new)
+        });
+
+        Self {
+            seq: Seq::new(bpm, played),
+            scaled,
+            patterns,
+            groove: Groove::new(),
+            bpm,
+            configs: preset.outputs.clone(),
+            rates: preset.rates,
+            rotations: preset.rotations,
+            swing: preset.swing,
+            swings: preset.swings,
+        }
+    }
+
+    /// Starts `preset` over from the top.
+    pub fn restart(&mut self, preset: &Preset) {
+        *self = Self::new(preset);
+    }
+
+    /// Takes everything from `preset` without starting over.
+    pub fn set_preset(&mut self, preset: &Preset) {
+        self.set_bpm(preset.bpm.whole());
+        preset.outputs.iter().enumerate().for_each(|(idx, config)| {
+            self.set_output_type(idx, config.output_type());
+            self.set_pwm(idx, config.pwm());
+            self.set_prob(idx, config.prob());
+            self.set_sequence(
+                idx,
+                config.length(),
+                config.density(),
+                preset.rotations[idx],
+            );
+            self.set_rate(idx, preset.rates[idx]);
+        });
+        self.swing = preset.swing;
+        self.swings = preset.swings;
+    }
+
+    /// Moves every output on a tick and returns which to toggle.
+    pub fn tick(&mut self) -> [bool; 4] {
+        self.seq.tick();
+        let toggled = core::array::from_fn(|idx| {
+            let pattern = &mut self.patterns[idx];
+            match &mut self.scaled[idx] {
+                Option::Some(scaled) => scaled.tick(pattern),
+                Option::None => {
+                    pattern.tick(self.seq.get_index_change(idx), self.seq.get_on_change(idx)) as u8
+                }
+            }
+        });
+
+        self.groove.tick(toggled, self.timings())
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.seq.resolution()
+    }
+
+    /// Whether output `idx` moved to its next step on the latest tick.
+    pub fn get_index_change(&self, idx: usize) -> bool {
+        match &self.scaled[idx] {
+            Option::Some(scaled) => scaled.index_change,
+            Option::None => self.seq.get_index_change(idx),
+        }
+    }
+
+    pub fn get_index(&self, idx: usize) -> usize {
+        self.patterns[idx].index
+    }
+
+    pub fn set_bpm(&mut self, bpm: u32) {
+        self.bpm = bpm;
+        self.seq.set_bpm(bpm);
+        self.each_scaled(|seq| seq.set_bpm(bpm));
+    }
+
+    pub fn set_sequence(
+        &mut self,
+        idx: usize,
+        length: Length,
+        density: Density,
+        rotation: Rotation,
+    ) {
+        self.configs[idx].set_sequence(length, density);
+        self.rotations[idx] = rotation;
+        self.patterns[idx].set_sequence(length, density);
+    }
+
+    pub fn set_output_type(&mut self, idx: usize, output_type: OutputType) {
+        self.configs[idx].set_output_type(output_type);
+        self.patterns[idx].euclid = output_type == OutputType::Euclid;
+    }
+
+    /// A ratio or note puts the output on a `Seq` of its own, starting from
+    /// its first step, unless it was on one already.
+    pub fn set_rate(&mut self, idx: usize, rate: StepRate) {
+        self.seq.set_rate(idx, rate.seq_rate());
+        self.configs[idx].set_rate(rate.seq_rate());
+        self.rates[idx] = rate;
+        self.scaled[idx] = match (rate.scaled(), self.scaled[idx].take()) {
+            (Option::Some((steps, beats)), Option::Some(mut scaled)) => {
+                scaled.steps = steps;
+                scaled.beats = beats;
+                Option::Some(scaled)
+            }
+            (Option::Some((steps, beats)), Option::None) => {
+                self.patterns[idx].index = 0;
+                Option::Some(Scaled::new(self.bpm, &self.configs[idx], steps, beats))
+            }
+            (Option::None, _) => Option::None,
+        };
+    }
+
+    pub fn set_prob(&mut self, idx: usize, prob: Prob) {
+        self.seq.set_prob(idx, prob);
+        self.configs[idx].set_prob(prob);
+        self.scaled_at(idx, |seq| seq.set_prob(0, prob));
+    }
+
+    pub fn set_pwm(&mut self, idx: usize, pwm: Pwm) {
+        self.seq.set_pwm(idx, pwm);
+        self.configs[idx].set_pwm(pwm);
+        self.scaled_at(idx, |seq| seq.set_pwm(0, pwm));
+    }
+
+    /// The swing of every output without one of its own.
+    pub fn set_swing(&mut self, swing: Swing) {
+        self.swing = swing;
+    }
+
+    /// Output `idx`'s own swing, or `None` to follow the global one.
+    pub fn set_output_swing(&mut self, idx: usize, swing: Option<Swing>) {
+        self.swings[idx] = swing;
+    }
+
+    fn scaled_at(&mut self, idx: usize, set: impl FnOnce(&mut Seq)) {
+        if let Option::Some(scaled) = &mut self.scaled[idx] {
+            set(&mut scaled.seq);
+        }
+    }
+
+    fn each_scaled(&mut self, mut set: impl FnMut(&mut Seq)) {
+        self.scaled
+            .iter_mut()
+            .flatten()
+            .for_each(|scaled| set(&mut scaled.seq));
+    }
+
+    /// Only euclid outputs are rotated.
+    fn timings(&self) -> [Timing; 4] {
+        let resolution = self.seq.resolution();
+        core::array::from_fn(|idx| {
+            let swing = self.swings[idx].unwrap_or(self.swing);
+            let step = groove::step_ticks(self.rates[idx], resolution);
+            let late = match self.configs.get(idx).map(OutputConfig::output_type) {
+                Option::Some(OutputType::Euclid) => {
+                    groove::steps_ticks(self.rates[idx], resolution, self.rotations[idx].0 as u64)
+                }
+                _ => 0,
+            };
+            Timing { swing, step, late }
+        })
+    }
+}
+
+/// An output on a ratio or note, played by a `Seq` of its own at one step a
+/// beat.
+struct Scaled {
+    seq: Seq,
+    steps: u32,
+    beats: u32,
+    // Steps towards its next tick, which it takes at `beats`.
+    progress: u32,
+    index_change: bool,
+}
+
+impl Scaled {
+    fn new(bpm: u32, config: &OutputConfig, steps: u32, beats: u32) -> Self {
+        let mut configs = Vec::new();
+        configs.push(played(config)).ok();
+
+        Self {
+            seq: Seq::new(bpm, configs),
+            steps,
+            beats,
+            progress: 0,
+            index_change: false,
+        }
+    }
+
+    /// Ticks as many times as a tick of the others comes to, `steps` over
+    /// `beats` of one, and returns how many times `pattern` toggled.
+    fn tick(&mut self, pattern: &mut Pattern) -> u8 {
+        let mut toggled = 0;
+        self.index_change = false;
+        self.progress += self.steps;
+        while self.progress >= self.beats {
+            self.progress -= self.beats;
+            self.seq.tick();
+            let index_change = self.seq.get_index_change(0);
+            toggled += pattern.tick(index_change, self.seq.get_on_change(0)) as u8;
+            self.index_change |= index_change;
+        }
+
+        toggled
+    }
+}
+
+/// Where an output is in its pattern, and whether its gate is up here and
+/// in `Seq`.
+#[derive(Default)]
+struct Pattern {
+    steps: Vec<bool, MAX_LENGTH>,
+    euclid: bool,
+    index: usize,
+    seq_high: bool,
+    high: bool,
+}
+
+impl Pattern {
+    fn new(config: &OutputConfig) -> Self {
+        let mut pattern = Self {
+            euclid: config.output_type() == OutputType::Euclid,
+            ..Self::default()
+        };
+        pattern.set_sequence(config.length(), config.density());
+        pattern
+    }
+
+    fn set_sequence(&mut self, length: Length, density: Density) {
+        euclid(density, length, &mut self.steps);
+        self.index %= self.steps.len().max(1);
+    }
+
+    /// Follows `Seq`'s gate on a tick and returns whether to toggle. A
+    /// euclid output's gate only goes up on a step that's on.
+    fn tick(&mut self, index_change: bool, toggled: bool) -> bool {
+        if index_change {
+            self.index = (self.index + 1) % self.steps.len().max(1);
+        }
+        self.seq_high ^= toggled;
+        let on = !self.euclid || self.steps.get(self.index).copied().unwrap_or(false);
+        let high = self.seq_high && on;
+        let toggle = high != self.high;
+        self.high = high;
+
+        toggle
+    }
+}
+
+/// `config` as `Seq` plays it: a gate, whatever the pattern.
+fn played(config: &OutputConfig) -> OutputConfig {
+    let gate = OutputConfig::new();
+    let mut played = config.clone();
+    played.set_output_type(OutputType::Gate);
+    played.set_sequence(gate.length(), gate.density());
+    played
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::State;
+
+    #[test]
+    fn plays_euclid_patterns_longer_than_seqs() {
+        let mut preset = State::default().preset();
+        preset.outputs[0].set_output_type(OutputType::Euclid);
+        preset.outputs[0].set_sequence(Length(64), Density(17));
+        let mut sequencer = Sequencer::new(&preset);
+        let mut expected = Vec::new();
+        euclid(Density(17), Length(64), &mut expected);
+
+        let mut high = false;
+        let mut played = [false; MAX_LENGTH];
+        for _ in 0..64 * sequencer.resolution() {
+            if sequencer.tick()[0] {
+                high = !high;
+                played[sequencer.get_index(0)] |= high;
+            }
+        }
+
+        assert_eq!(played.as_slice(), expected.as_slice());
+    }
+}
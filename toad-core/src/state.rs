@@ -0,0 +1,101 @@
+use seq::OutputConfig;
+
+pub use self::{
+    acceleration::Acceleration,
+    bpm::{Bpm, MAX_BPM, MIN_BPM},
+    bpm_sync::{Averaging, BpmSync, Rounding, TapSettings, MAX_TAP_WINDOW},
+    button::Button,
+    command::Command,
+    edit_mode::EditMode,
+    element::Element,
+    length::MAX_LENGTH,
+    lock::Lock,
+    name::{Name, NAME_LENGTH},
+    output::Output,
+    output_type::OutputTypeString,
+    play_status::PlayStatus,
+    ppqn::Ppqn,
+    preset::{Preset, PRESETS},
+    prob::ProbString,
+    rate::{ratio, RateString, StepRate, MAX_RATIO, RATES},
+    rotation::Rotation,
+    screen::Screen,
+    screen_state::{HomeScreenState, OutputScreenState, PresetsScreenState, ScreenState},
+    sequence_state::SequenceState,
+    slot::Slot,
+    state::State,
+    state_change::StateChange,
+    swing::{Swing, SwingString, MAX_SWING, MIN_SWING},
+    sync::Sync,
+};
+
+mod acceleration;
+mod bpm;
+mod bpm_sync;
+mod button;
+mod command;
+mod density;
+mod edit_mode;
+mod element;
+mod length;
+mod lock;
+mod name;
+mod output;
+mod output_type;
+mod play_status;
+mod ppqn;
+mod preset;
+mod prob;
+mod pwm;
+mod rate;
+mod rotation;
+mod screen;
+mod screen_state;
+mod sequence_state;
+mod slot;
+#[allow(clippy::module_inception)]
+mod state;
+mod state_change;
+mod swing;
+mod sync;
+
+trait Updatable {
+    fn next(&self) -> Option<Self>
+    where
+        Self: Sized;
+    fn prev(&self) -> Option<Self>
+    where
+        Self: Sized;
+
+    /// Up to `steps` of `next`, or `None` if there's no next at all.
+    fn next_by(&self, steps: u32) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let mut next = self.next()?;
+        for _ in 1..steps {
+            match next.next() {
+                Option::Some(further) => next = further,
+                Option::None => break,
+            }
+        }
+
+        Option::Some(next)
+    }
+
+    /// Up to `steps` of `prev`, or `None` if there's no prev at all.
+    fn prev_by(&self, steps: u32) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let mut prev = self.prev()?;
+        for _ in 1..steps {
+            match prev.prev() {
+                Option::Some(further) => prev = further,
+                Option::None => break,
+            }
+        }
+
+        Option::Some(prev)
+    }
+}
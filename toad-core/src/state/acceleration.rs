@@ -0,0 +1,28 @@
+// Detents closer together than this, in microseconds, move this many steps.
+const CURVE: [(u64, u32); 3] = [(15_000, 8), (30_000, 4), (60_000, 2)];
+
+/// Turns the time between encoder detents into how many steps each one
+/// moves, so spinning the encoder covers more ground than turning it.
+/// Changing direction starts slow again.
+#[derive(Clone, Default)]
+pub struct Acceleration {
+    last: Option<(u64, bool)>,
+}
+
+impl Acceleration {
+    pub fn steps(&mut self, now: u64, right: bool) -> u32 {
+        let steps = match self.last {
+            Option::Some((last, was_right)) if was_right == right => {
+                let interval = now.saturating_sub(last);
+                CURVE
+                    .iter()
+                    .find(|(within, _)| interval < *within)
+                    .map_or(1, |(_, steps)| *steps)
+            }
+            _ => 1,
+        };
+        self.last = Option::Some((now, right));
+
+        steps
+    }
+}
@@ -0,0 +1,72 @@
+use core::fmt;
+
+use super::Updatable;
+
+pub const MIN_BPM: u32 = 1;
+pub const MAX_BPM: u32 = 300;
+
+const TENTHS_IN_A_MINUTE: u64 = 600_000_000; // in microseconds
+
+/// Tenths of a BPM.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Bpm(pub u32);
+
+impl Bpm {
+    pub const fn from_whole(bpm: u32) -> Self {
+        Self(bpm * 10)
+    }
+
+    /// Rounded to the nearest whole BPM, which is all `Seq` takes.
+    pub fn whole(&self) -> u32 {
+        (self.0 + 5) / 10
+    }
+
+    /// How long a tick is with `resolution` of them to a beat. Use this
+    /// rather than `Seq`'s, which only knows the whole BPM.
+    pub fn tick_duration_micros(&self, resolution: u32) -> u64 {
+        TENTHS_IN_A_MINUTE / (self.0 as u64 * resolution as u64).max(1)
+    }
+
+    pub fn next_tenths(&self, steps: u32) -> Option<Self> {
+        self.step(steps as i32)
+    }
+
+    pub fn prev_tenths(&self, steps: u32) -> Option<Self> {
+        self.step(-(steps as i32))
+    }
+
+    fn step(&self, tenths: i32) -> Option<Self> {
+        let (min, max) = (MIN_BPM * 10, MAX_BPM * 10);
+        let next = self.0.saturating_add_signed(tenths).clamp(min, max);
+
+        Option::Some(Self(next)).filter(|next| *next != *self)
+    }
+}
+
+impl fmt::Display for Bpm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 % 10 {
+            0 => write!(f, "{}", self.0 / 10),
+            tenths => write!(f, "{}.{}", self.0 / 10, tenths),
+        }
+    }
+}
+
+impl Updatable for Bpm {
+    fn next(&self) -> Option<Self> {
+        self.step(10)
+    }
+
+    fn prev(&self) -> Option<Self> {
+        self.step(-10)
+    }
+
+    fn next_by(&self, steps: u32) -> Option<Self> {
+        self.step(steps as i32 * 10)
+    }
+
+    fn prev_by(&self, steps: u32) -> Option<Self> {
+        self.step(-(steps as i32 * 10))
+    }
+}
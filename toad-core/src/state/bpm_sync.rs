@@ -0,0 +1,299 @@
+use heapless::Vec;
+
+use super::{MAX_BPM, MIN_BPM};
+
+const TENTHS_IN_A_MINUTE: u64 = 600_000_000; // in microseconds
+const TOO_LONG_IN_MIRCO_SECONDS: u64 = 5_000_000; // 5 seconds
+pub const MAX_TAP_WINDOW: usize = 8;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Averaging {
+    Median,
+    /// Newer taps count for more.
+    Weighted,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rounding {
+    Whole,
+    Tenths,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TapSettings {
+    /// How many of the latest intervals to average, up to `MAX_TAP_WINDOW`.
+    pub window: usize,
+    pub averaging: Averaging,
+    pub rounding: Rounding,
+    /// With the internal clock, start the sequences over on each tap so
+    /// the last one lands on the downbeat.
+    pub realign: bool,
+}
+
+impl TapSettings {
+    /// What the module taps with.
+    pub const DEFAULT: Self = Self {
+        window: 6,
+        averaging: Averaging::Median,
+        rounding: Rounding::Whole,
+        realign: true,
+    };
+}
+
+impl Default for TapSettings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Turns taps into a tempo. An interval more than a third off the current
+/// estimate, like a missed or doubled tap, is left out. If it and the next
+/// add up to about the estimate, a tap landed in between and the two count
+/// as one interval, but not twice running, so tapping twice as fast isn't
+/// mistaken for extra taps for ever. Two in a row that agree with each other
+/// are taken as a new tempo and averaging starts again from there.
+///
+/// Timestamps are microseconds from any fixed point, so this doesn't care
+/// where the time comes from.
+#[derive(Clone)]
+pub struct BpmSync {
+    settings: TapSettings,
+    last: u64,
+    intervals: Vec<u64, MAX_TAP_WINDOW>,
+    rejected: Option<u64>,
+    /// Whether the latest interval was two joined together.
+    joined: bool,
+}
+
+impl BpmSync {
+    pub fn new(settings: TapSettings, now: u64) -> Self {
+        Self {
+            settings,
+            last: now,
+            intervals: Vec::new(),
+            rejected: Option::None,
+            joined: false,
+        }
+    }
+
+    /// Takes a tap and returns the tempo in tenths of a BPM, if the tap
+    /// counted towards one.
+    pub fn pulse(&mut self, now: u64) -> Option<u32> {
+        let interval = now.saturating_sub(self.last);
+        self.last = now;
+
+        if interval >= TOO_LONG_IN_MIRCO_SECONDS || interval == 0 {
+            self.intervals.clear();
+            self.rejected = Option::None;
+            self.joined = false;
+
+            return Option::None
+        }
+
+        let rejected = self.rejected.take();
+        if let Option::Some(estimate) = self.estimate() {
+            let off = |interval: u64| interval.abs_diff(estimate);
+            match rejected {
+                Option::Some(rejected)
+                    if !self.joined
+                        && off(rejected + interval) <= estimate / 3
+                        && off(rejected + interval) < off(interval) =>
+                {
+                    self.push(rejected + interval);
+                    self.joined = true;
+                }
+                _ if off(interval) <= estimate / 3 => self.push(interval),
+                Option::Some(rejected) if rejected.abs_diff(interval) <= rejected / 3 => {
+                    self.intervals.clear();
+                    self.push(rejected);
+                    self.push(interval);
+                }
+                _ => {
+                    self.rejected = Option::Some(interval);
+                    return Option::None
+                }
+            }
+        } else {
+            self.push(interval);
+        }
+
+        self.estimate().map(|estimate| self.tenths(estimate))
+    }
+
+    fn push(&mut self, interval: u64) {
+        self.joined = false;
+        if self.intervals.len() >= self.window() {
+            self.intervals.rotate_left(1);
+            self.intervals.truncate(self.window() - 1);
+        }
+        self.intervals.push(interval).ok();
+    }
+
+    fn window(&self) -> usize {
+        self.settings.window.clamp(1, MAX_TAP_WINDOW)
+    }
+
+    /// Microseconds per beat.
+    fn estimate(&self) -> Option<u64> {
+        if self.intervals.is_empty() {
+            return Option::None
+        }
+
+        let estimate = match self.settings.averaging {
+            Averaging::Median => {
+                let mut sorted = self.intervals.clone();
+                sorted.sort_unstable();
+                let mid = sorted.len() / 2;
+                if sorted.len() % 2 == 0 {
+                    (sorted[mid - 1] + sorted[mid]) / 2
+                } else {
+                    sorted[mid]
+                }
+            }
+            Averaging::Weighted => {
+                let (sum, weights) = self.intervals.iter().zip(1..).fold(
+                    (0, 0),
+                    |(sum, weights), (interval, weight)| {
+                        (sum + interval * weight, weights + weight)
+                    },
+                );
+                sum / weights
+            }
+        };
+
+        Option::Some(estimate)
+    }
+
+    fn tenths(&self, estimate: u64) -> u32 {
+        let tenths = (TENTHS_IN_A_MINUTE + estimate / 2) / estimate;
+        let tenths = match self.settings.rounding {
+            Rounding::Whole => (tenths + 5) / 10 * 10,
+            Rounding::Tenths => tenths,
+        };
+
+        (tenths as u32).clamp(MIN_BPM * 10, MAX_BPM * 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TENTHS: TapSettings = TapSettings {
+        window: 6,
+        averaging: Averaging::Median,
+        rounding: Rounding::Tenths,
+        realign: false,
+    };
+
+    /// Taps `intervals` apart, in milliseconds, and returns what each tap
+    /// after the first came to.
+    fn taps<const N: usize>(settings: TapSettings, intervals: [u64; N]) -> [Option<u32>; N] {
+        let mut now = 1_000_000;
+        let mut bpm_sync = BpmSync::new(settings, now);
+
+        intervals.map(|interval| {
+            now += interval * 1_000;
+            bpm_sync.pulse(now)
+        })
+    }
+
+    #[test]
+    fn steady_taps_give_their_tempo() {
+        let tempos = taps(TENTHS, [500, 500, 500]);
+
+        assert_eq!(tempos, [Option::Some(1_200); 3]);
+    }
+
+    #[test]
+    fn rounds_to_whole_or_tenths() {
+        let whole = TapSettings {
+            rounding: Rounding::Whole,
+            ..TENTHS
+        };
+
+        assert_eq!(taps(TENTHS, [502]), [Option::Some(1_195)]);
+        assert_eq!(taps(whole, [502]), [Option::Some(1_200)]);
+    }
+
+    #[test]
+    fn averages_over_the_window() {
+        let short = TapSettings {
+            window: 2,
+            ..TENTHS
+        };
+
+        assert_eq!(
+            taps(TENTHS, [500, 500, 500, 520, 520])[4],
+            Option::Some(1_200)
+        );
+        assert_eq!(
+            taps(short, [500, 500, 500, 520, 520])[4],
+            Option::Some(1_154)
+        );
+    }
+
+    #[test]
+    fn weighted_averaging_favours_newer_taps() {
+        let weighted = TapSettings {
+            averaging: Averaging::Weighted,
+            ..TENTHS
+        };
+
+        // Median of 500 and 520 is 510, weighted is 513.
+        assert_eq!(taps(TENTHS, [500, 520])[1], Option::Some(1_176));
+        assert_eq!(taps(weighted, [500, 520])[1], Option::Some(1_169));
+    }
+
+    #[test]
+    fn tap_between_beats_is_left_out() {
+        let tempos = taps(TENTHS, [500, 500, 250, 250, 500, 500]);
+
+        assert_eq!(
+            tempos,
+            [
+                Option::Some(1_200),
+                Option::Some(1_200),
+                Option::None,
+                Option::Some(1_200),
+                Option::Some(1_200),
+                Option::Some(1_200),
+            ]
+        );
+    }
+
+    #[test]
+    fn double_tap_is_left_out() {
+        let tempos = taps(TENTHS, [500, 500, 40, 460, 500]);
+
+        assert_eq!(tempos[2], Option::None);
+        assert_eq!(tempos[3], Option::Some(1_200));
+        assert_eq!(tempos[4], Option::Some(1_200));
+    }
+
+    #[test]
+    fn missed_tap_is_left_out() {
+        let tempos = taps(TENTHS, [500, 500, 1_000, 500]);
+
+        assert_eq!(tempos[2], Option::None);
+        assert_eq!(tempos[3], Option::Some(1_200));
+    }
+
+    #[test]
+    fn tapping_twice_as_fast_is_a_new_tempo() {
+        let tempos = taps(TENTHS, [500, 500, 250, 250, 250, 250]);
+
+        assert_eq!(tempos[5], Option::Some(2_400));
+    }
+
+    #[test]
+    fn long_gap_starts_over() {
+        let tempos = taps(TENTHS, [500, 500, 6_000, 400]);
+
+        assert_eq!(tempos[2], Option::None);
+        assert_eq!(tempos[3], Option::Some(1_500));
+    }
+}
@@ -0,0 +1,29 @@
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Button {
+    Bpm,
+    Play,
+    Page,
+    Encoder,
+}
+
+impl Button {
+    pub const ALL: [Button; 4] = [Button::Bpm, Button::Play, Button::Page, Button::Encoder];
+
+    /// Only the encoder does anything on a double press, and every other
+    /// button's press would wait out the double press time for nothing.
+    pub fn double_presses(&self) -> bool {
+        matches!(self, Button::Encoder)
+    }
+}
+
+impl From<Button> for usize {
+    fn from(val: Button) -> Self {
+        match val {
+            Button::Bpm => 0,
+            Button::Play => 1,
+            Button::Page => 2,
+            Button::Encoder => 3,
+        }
+    }
+}
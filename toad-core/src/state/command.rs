@@ -0,0 +1,33 @@
+use embassy_time::Instant;
+
+use super::Button;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    /// Turns carry when they happened, for acceleration.
+    EncoderRight(Instant),
+    EncoderLeft(Instant),
+    EncoderPress,
+    PagePress,
+    PlayPress,
+    /// Carries when the button went down, so taps are timed from the button
+    /// rather than from whenever the command gets handled.
+    BpmPress(Instant),
+    LongPress(Button),
+    DoublePress(Button),
+    /// The first button is the one that was already down.
+    Chord(Button, Button),
+}
+
+impl Command {
+    /// A plain press of `button` that went down `at`.
+    pub fn press(button: Button, at: Instant) -> Self {
+        match button {
+            Button::Bpm => Command::BpmPress(at),
+            Button::Play => Command::PlayPress,
+            Button::Page => Command::PagePress,
+            Button::Encoder => Command::EncoderPress,
+        }
+    }
+}
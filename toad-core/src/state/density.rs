@@ -1,10 +1,10 @@
 use seq::Density;
 
-use super::Updatable;
+use super::{Updatable, MAX_LENGTH};
 
 impl Updatable for Density {
     fn next(&self) -> Option<Self> {
-        if self.0 == 16 {
+        if self.0 as usize == MAX_LENGTH {
             Option::None
         } else {
             Option::Some(Self(self.0 + 1))
@@ -0,0 +1,28 @@
+const COARSE_STEPS: u32 = 10;
+
+/// How far each encoder detent moves a value.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EditMode {
+    Normal,
+    /// BPM moves by tenths.
+    Fine,
+    Coarse,
+}
+
+impl EditMode {
+    pub fn cycle(&self) -> Self {
+        match self {
+            EditMode::Normal => EditMode::Fine,
+            EditMode::Fine => EditMode::Coarse,
+            EditMode::Coarse => EditMode::Normal,
+        }
+    }
+
+    pub fn steps(&self, steps: u32) -> u32 {
+        match self {
+            EditMode::Normal | EditMode::Fine => steps,
+            EditMode::Coarse => steps * COARSE_STEPS,
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use seq::Density;
+
+use super::{
+    rate::{next_beats, prev_beats},
+    EditMode, Screen, ScreenState, SequenceState, State, StateChange, Updatable,
+};
+
+#[derive(Clone)]
+pub enum Element {
+    Rate,
+    /// The beats a ratio rate spreads its steps over.
+    Beats,
+    Pwm,
+    Prob,
+    Length,
+    Density,
+    Rotation,
+    Swing,
+    OutputType,
+    Bpm,
+    Sync,
+    Ppqn,
+    Slot,
+    Name,
+    /// Loads the slot on a press; turning picks between this and `Save`.
+    Load,
+    /// Saves to the slot on a press.
+    Save,
+}
+
+impl Element {
+    /// Moves the element's value `steps` on, for the values that go in steps.
+    pub fn next(&self, state: &mut State, steps: u32) -> Option<StateChange> {
+        match self {
+            Element::Bpm => match state.edit_mode {
+                EditMode::Fine => state.bpm.next_tenths(steps),
+                EditMode::Normal | EditMode::Coarse => state.bpm.next_by(steps),
+            }
+            .map(|bpm| {
+                state.bpm = bpm;
+                state.bpm_sync = Option::None;
+                StateChange::Bpm(bpm)
+            }),
+            Element::Sync => state.sync.next().map(|sync| {
+                state.sync = sync;
+                state.bpm_sync = Option::None;
+                StateChange::Sync(sync)
+            }),
+            Element::Ppqn => state.ppqn.next().map(|ppqn| {
+                state.ppqn = ppqn;
+                StateChange::Ppqn(ppqn)
+            }),
+            Element::Swing if matches!(state.current_screen, Screen::Home) => {
+                state.swing.next().map(|swing| {
+                    state.swing = swing;
+                    StateChange::Swing(Screen::Home, Option::Some(swing))
+                })
+            }
+            Element::Slot => state
+                .preset_slot
+                .next()
+                .map(|slot| state.select_preset(slot)),
+            Element::Name => state
+                .preset_name
+                .next_char(state.name_char, steps)
+                .map(|name| state.rename_preset(name)),
+            Element::Load => Option::Some(state.pick_action(Element::Save)),
+            Element::Save => Option::None,
+            elem => {
+                let output = match state.current_screen {
+                    Screen::Home | Screen::Presets => unreachable!(),
+                    Screen::Output(output, _) => output,
+                };
+                let config = &mut state.outputs[usize::from(output)];
+                let rate = &mut state.rates[usize::from(output)];
+                let rotation = &mut state.rotations[usize::from(output)];
+                let swing = &mut state.swings[usize::from(output)];
+                match elem {
+                    Element::Rate => rate.next().map(|next| {
+                        *rate = next;
+                        config.set_rate(next.seq_rate());
+                        StateChange::Rate(output, config.output_type(), next)
+                    }),
+                    Element::Beats => next_beats(*rate).map(|next| {
+                        *rate = next;
+                        StateChange::Rate(output, config.output_type(), next)
+                    }),
+                    Element::Pwm => config.pwm().next().map(|pwm| {
+                        config.set_pwm(pwm);
+                        StateChange::Pwm(output, pwm)
+                    }),
+                    Element::Prob => config.prob().next().map(|prob| {
+                        config.set_prob(prob);
+                        StateChange::Prob(output, prob)
+                    }),
+                    Element::Length => config.length().next_by(steps).map(|length| {
+                        config.set_sequence(length, config.density());
+                        SequenceState::new(output, length, config.density(), *rotation).into()
+                    }),
+                    Element::Density => config.density().next_by(steps).map(|density| {
+                        let length = config.length();
+                        let density = Density(density.0.min(length.0));
+                        config.set_sequence(length, density);
+                        SequenceState::new(output, length, density, *rotation).into()
+                    }),
+                    Element::Rotation => rotation
+                        .next_by(steps)
+                        .map(|next| next.within(config.length().0 as u32))
+                        .filter(|next| *next != *rotation)
+                        .map(|next| {
+                            *rotation = next;
+                            SequenceState::new(output, config.length(), config.density(), next)
+                                .into()
+                        }),
+                    Element::Swing => swing.next().map(|next| {
+                        *swing = next;
+                        StateChange::Swing(state.current_screen, next)
+                    }),
+                    Element::OutputType => config.output_type().next().map(|output_type| {
+                        config.set_output_type(output_type);
+                        StateChange::OutputType(ScreenState::new_output(
+                            output,
+                            config.clone(),
+                            *rate,
+                            *rotation,
+                            *swing,
+                            state.edit_mode,
+                            Option::None,
+                        ))
+                    }),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    pub fn prev(&self, state: &mut State, steps: u32) -> Option<StateChange> {
+        match self {
+            Element::Bpm => match state.edit_mode {
+                EditMode::Fine => state.bpm.prev_tenths(steps),
+                EditMode::Normal | EditMode::Coarse => state.bpm.prev_by(steps),
+            }
+            .map(|bpm| {
+                state.bpm = bpm;
+                StateChange::Bpm(bpm)
+            }),
+            Element::Sync => state.sync.prev().map(|sync| {
+                state.sync = sync;
+                StateChange::Sync(sync)
+            }),
+            Element::Ppqn => state.ppqn.prev().map(|ppqn| {
+                state.ppqn = ppqn;
+                StateChange::Ppqn(ppqn)
+            }),
+            Element::Swing if matches!(state.current_screen, Screen::Home) => {
+                state.swing.prev().map(|swing| {
+                    state.swing = swing;
+                    StateChange::Swing(Screen::Home, Option::Some(swing))
+                })
+            }
+            Element::Slot => state
+                .preset_slot
+                .prev()
+                .map(|slot| state.select_preset(slot)),
+            Element::Name => state
+                .preset_name
+                .prev_char(state.name_char, steps)
+                .map(|name| state.rename_preset(name)),
+            Element::Load => Option::None,
+            Element::Save => Option::Some(state.pick_action(Element::Load)),
+            elem => {
+                let output = match state.current_screen {
+                    Screen::Home | Screen::Presets => unreachable!(),
+                    Screen::Output(output, _) => output,
+                };
+                let config = &mut state.outputs[usize::from(output)];
+                let rate = &mut state.rates[usize::from(output)];
+                let rotation = &mut state.rotations[usize::from(output)];
+                let swing = &mut state.swings[usize::from(output)];
+                match elem {
+                    Element::Rate => rate.prev().map(|next| {
+                        *rate = next;
+                        config.set_rate(next.seq_rate());
+                        StateChange::Rate(output, config.output_type(), next)
+                    }),
+                    Element::Beats => prev_beats(*rate).map(|next| {
+                        *rate = next;
+                        StateChange::Rate(output, config.output_type(), next)
+                    }),
+                    Element::Pwm => config.pwm().prev().map(|pwm| {
+                        config.set_pwm(pwm);
+                        StateChange::Pwm(output, pwm)
+                    }),
+                    Element::Prob => config.prob().prev().map(|prob| {
+                        config.set_prob(prob);
+                        StateChange::Prob(output, prob)
+                    }),
+                    Element::Length => config.length().prev_by(steps).map(|length| {
+                        let density = Density(config.density().0.min(length.0));
+                        config.set_sequence(length, density);
+                        *rotation = rotation.within(length.0 as u32);
+                        SequenceState::new(output, length, density, *rotation).into()
+                    }),
+                    Element::Density => config.density().prev_by(steps).map(|density| {
+                        config.set_sequence(config.length(), density);
+                        SequenceState::new(output, config.length(), density, *rotation).into()
+                    }),
+                    Element::Rotation => rotation.prev_by(steps).map(|prev| {
+                        *rotation = prev;
+                        SequenceState::new(output, config.length(), config.density(), prev).into()
+                    }),
+                    Element::Swing => swing.prev().map(|prev| {
+                        *swing = prev;
+                        StateChange::Swing(state.current_screen, prev)
+                    }),
+                    Element::OutputType => config.output_type().prev().map(|output_type| {
+                        config.set_output_type(output_type);
+                        StateChange::OutputType(ScreenState::new_output(
+                            output,
+                            config.clone(),
+                            *rate,
+                            *rotation,
+                            *swing,
+                            state.edit_mode,
+                            Option::None,
+                        ))
+                    }),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+}
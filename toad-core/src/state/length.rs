@@ -2,9 +2,11 @@ use seq::Length;
 
 use super::Updatable;
 
+pub const MAX_LENGTH: usize = 64;
+
 impl Updatable for Length {
     fn next(&self) -> Option<Self> {
-        if self.0 == 16 {
+        if self.0 as usize == MAX_LENGTH {
             Option::None
         } else {
             Option::Some(Self(self.0 + 1))
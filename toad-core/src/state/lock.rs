@@ -0,0 +1,21 @@
+use core::fmt;
+
+/// How well the external clock is being followed.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Lock {
+    Locked,
+    /// The clock stopped, carrying on at its last tempo for a while.
+    Freewheel,
+    Lost,
+}
+
+impl fmt::Display for Lock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Locked => write!(f, "Lock"),
+            Self::Freewheel => write!(f, "Free"),
+            Self::Lost => write!(f, "Lost"),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use core::fmt;
+
+pub const NAME_LENGTH: usize = 6;
+
+/// What a name is spelled from, blank first.
+const CHARS: &[u8] = b" ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-";
+
+/// A preset's name, edited a character at a time.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Name(pub [u8; NAME_LENGTH]);
+
+impl Default for Name {
+    fn default() -> Self {
+        Self([CHARS[0]; NAME_LENGTH])
+    }
+}
+
+impl Name {
+    /// The name in `bytes`, if every one of them is a name character.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut name = Self::default();
+        for (char, byte) in name.0.iter_mut().zip(bytes) {
+            if !CHARS.contains(byte) {
+                return Option::None
+            }
+            *char = *byte;
+        }
+
+        Option::Some(name)
+    }
+
+    /// The name with the character `at` moved `steps` on, or `None` at the
+    /// last character.
+    pub fn next_char(&self, at: usize, steps: u32) -> Option<Self> {
+        let position = Self::position(self.0[at]);
+        if position == CHARS.len() - 1 {
+            return Option::None
+        }
+        let mut name = *self;
+        name.0[at] = CHARS[(position + steps as usize).min(CHARS.len() - 1)];
+
+        Option::Some(name)
+    }
+
+    /// The name with the character `at` moved `steps` back, or `None` at a
+    /// blank.
+    pub fn prev_char(&self, at: usize, steps: u32) -> Option<Self> {
+        let position = Self::position(self.0[at]);
+        if position == 0 {
+            return Option::None
+        }
+        let mut name = *self;
+        name.0[at] = CHARS[position.saturating_sub(steps as usize)];
+
+        Option::Some(name)
+    }
+
+    fn position(char: u8) -> usize {
+        CHARS.iter().position(|c| *c == char).unwrap_or(0)
+    }
+}
+
+/// Blanks show as underscores, so the screen has something to point at.
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|char| match char {
+            b' ' => write!(f, "_"),
+            char => write!(f, "{}", *char as char),
+        })
+    }
+}
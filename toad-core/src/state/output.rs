@@ -1,8 +1,7 @@
 use core::fmt;
 
-use defmt::Format;
-
-#[derive(Clone, Copy, Eq, Format, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Output {
     A,
     B,
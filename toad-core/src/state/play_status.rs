@@ -0,0 +1,9 @@
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PlayStatus {
+    Playing,
+    /// Outputs held low, every sequence staying where it is.
+    Paused,
+    /// Like paused, but every sequence is back at its first step.
+    Stopped,
+}
@@ -0,0 +1,64 @@
+use core::fmt;
+
+use super::Updatable;
+
+/// Pulses per quarter note on the clock input.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Ppqn {
+    P1,
+    P2,
+    P4,
+    P8,
+    P24,
+    P48,
+}
+
+impl Default for Ppqn {
+    fn default() -> Self {
+        Ppqn::P4
+    }
+}
+
+impl Ppqn {
+    pub fn pulses(&self) -> u32 {
+        match self {
+            Ppqn::P1 => 1,
+            Ppqn::P2 => 2,
+            Ppqn::P4 => 4,
+            Ppqn::P8 => 8,
+            Ppqn::P24 => 24,
+            Ppqn::P48 => 48,
+        }
+    }
+}
+
+impl Updatable for Ppqn {
+    fn next(&self) -> Option<Self> {
+        match self {
+            Ppqn::P48 => Option::None,
+            Ppqn::P24 => Option::Some(Ppqn::P48),
+            Ppqn::P8 => Option::Some(Ppqn::P24),
+            Ppqn::P4 => Option::Some(Ppqn::P8),
+            Ppqn::P2 => Option::Some(Ppqn::P4),
+            Ppqn::P1 => Option::Some(Ppqn::P2),
+        }
+    }
+
+    fn prev(&self) -> Option<Self> {
+        match self {
+            Ppqn::P1 => Option::None,
+            Ppqn::P2 => Option::Some(Ppqn::P1),
+            Ppqn::P4 => Option::Some(Ppqn::P2),
+            Ppqn::P8 => Option::Some(Ppqn::P4),
+            Ppqn::P24 => Option::Some(Ppqn::P8),
+            Ppqn::P48 => Option::Some(Ppqn::P24),
+        }
+    }
+}
+
+impl fmt::Display for Ppqn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pulses())
+    }
+}
@@ -0,0 +1,20 @@
+use heapless::Vec;
+use seq::OutputConfig;
+
+use super::{Bpm, Name, Ppqn, Rotation, StepRate, Swing, Sync};
+
+pub const PRESETS: usize = 8;
+
+#[derive(Clone)]
+pub struct Preset {
+    pub name: Name,
+    pub bpm: Bpm,
+    pub sync: Sync,
+    pub ppqn: Ppqn,
+    pub swing: Swing,
+    /// Each output's rate, of which `outputs` only hold what `Seq` plays.
+    pub outputs: Vec<OutputConfig, 4>,
+    pub rates: [StepRate; 4],
+    pub rotations: [Rotation; 4],
+    pub swings: [Option<Swing>; 4],
+}
@@ -0,0 +1,190 @@
+use core::fmt::Write;
+
+use heapless::String;
+use seq::{Frac, Rate};
+
+use super::Updatable;
+
+/// Either side of a ratio rate goes up to this.
+pub const MAX_RATIO: u32 = 16;
+
+/// How fast an output steps: one of the rates `Seq` plays, or steps to
+/// beats that the sequencer plays on top of it. A `Note` is a dotted or
+/// triplet note from the table, a `Ratio` one edited by hand.
+#[derive(Clone, Copy, PartialEq)]
+pub enum StepRate {
+    Seq(Rate),
+    Note(u32, u32),
+    Ratio(u32, u32),
+}
+
+impl StepRate {
+    /// What `Seq` is set to, one step a beat for notes and ratios.
+    pub fn seq_rate(&self) -> Rate {
+        match self {
+            StepRate::Seq(rate) => *rate,
+            StepRate::Note(..) | StepRate::Ratio(..) => Rate::Unity,
+        }
+    }
+
+    /// Steps to beats, for the rates played on top of `Seq`.
+    pub fn scaled(&self) -> Option<(u32, u32)> {
+        match *self {
+            StepRate::Seq(_) => Option::None,
+            StepRate::Note(steps, beats) | StepRate::Ratio(steps, beats) => {
+                Option::Some((steps, beats))
+            }
+        }
+    }
+}
+
+/// Slowest to fastest. `seq` only steps whole multiples and divisions of a
+/// beat, so the dotted and triplet notes are steps to beats, counting a beat
+/// as a quarter note.
+pub const RATES: [StepRate; 24] = [
+    StepRate::Seq(Rate::Div(64, Frac::Zero)),
+    StepRate::Seq(Rate::Div(32, Frac::Zero)),
+    StepRate::Seq(Rate::Div(16, Frac::Zero)),
+    StepRate::Seq(Rate::Div(8, Frac::Zero)),
+    StepRate::Seq(Rate::Div(5, Frac::Zero)),
+    StepRate::Seq(Rate::Div(4, Frac::Zero)),
+    StepRate::Seq(Rate::Div(3, Frac::Zero)),
+    StepRate::Note(3, 8),
+    StepRate::Seq(Rate::Div(2, Frac::Zero)),
+    StepRate::Note(2, 3),
+    StepRate::Note(3, 4),
+    StepRate::Seq(Rate::Unity),
+    StepRate::Note(4, 3),
+    StepRate::Note(3, 2),
+    StepRate::Seq(Rate::Mult(2, Frac::Zero)),
+    StepRate::Note(8, 3),
+    StepRate::Seq(Rate::Mult(3, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(4, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(5, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(8, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(16, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(32, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(64, Frac::Zero)),
+    StepRate::Seq(Rate::Mult(96, Frac::Zero)),
+];
+
+/// Ratio rates step their number of steps, the beats they're spread over
+/// are a separate element.
+impl Updatable for StepRate {
+    fn next(&self) -> Option<Self> {
+        if let StepRate::Ratio(steps, beats) = *self {
+            return (steps < MAX_RATIO).then_some(StepRate::Ratio(steps + 1, beats))
+        }
+        if *self == *RATES.last().unwrap() {
+            Option::None
+        } else {
+            let index = RATES.iter().position(|r| r == self).unwrap() + 1;
+            Option::Some(RATES[index])
+        }
+    }
+
+    fn prev(&self) -> Option<Self> {
+        if let StepRate::Ratio(steps, beats) = *self {
+            return (steps > 1).then_some(StepRate::Ratio(steps - 1, beats))
+        }
+        if *self == *RATES.first().unwrap() {
+            Option::None
+        } else {
+            let index = RATES.iter().position(|r| r == self).unwrap() - 1;
+            Option::Some(RATES[index])
+        }
+    }
+}
+
+pub(super) fn next_beats(rate: StepRate) -> Option<StepRate> {
+    match rate {
+        StepRate::Ratio(steps, beats) if beats < MAX_RATIO => {
+            Option::Some(StepRate::Ratio(steps, beats + 1))
+        }
+        _ => Option::None,
+    }
+}
+
+pub(super) fn prev_beats(rate: StepRate) -> Option<StepRate> {
+    match rate {
+        StepRate::Ratio(steps, beats) if beats > 1 => {
+            Option::Some(StepRate::Ratio(steps, beats - 1))
+        }
+        _ => Option::None,
+    }
+}
+
+/// Swaps a rate from the table for the same as a ratio, as near as one
+/// fits, and a ratio for the first rate in the table at least as fast.
+pub(super) fn toggle_ratio(rate: StepRate) -> StepRate {
+    match rate {
+        StepRate::Ratio(steps, beats) => *RATES
+            .iter()
+            .find(|rate| {
+                let (rate_steps, rate_beats) = ratio(**rate);
+                rate_steps * beats as u64 >= steps as u64 * rate_beats
+            })
+            .unwrap_or(RATES.last().unwrap()),
+        rate => {
+            let (steps, beats) = ratio(rate);
+            let max = MAX_RATIO as u64;
+            StepRate::Ratio(steps.min(max) as u32, beats.min(max) as u32)
+        }
+    }
+}
+
+/// Steps to beats, so `x2` is 2 to 1 and `4.` is 2 to 3.
+pub fn ratio(rate: StepRate) -> (u64, u64) {
+    match rate {
+        StepRate::Note(steps, beats) | StepRate::Ratio(steps, beats) => {
+            (steps as u64, beats as u64)
+        }
+        StepRate::Seq(Rate::Div(n, _)) => (1, n as u64),
+        StepRate::Seq(Rate::Unity) => (1, 1),
+        StepRate::Seq(Rate::Mult(n, _)) => (n as u64, 1),
+    }
+}
+
+/// Dotted and triplet rates are labelled as the note they play, `4.` for a
+/// dotted quarter and `4t` for a quarter note triplet, and ratios as steps
+/// over beats.
+pub struct RateString(pub String<5>);
+
+impl From<StepRate> for RateString {
+    fn from(val: StepRate) -> Self {
+        let rate_string = match val {
+            StepRate::Ratio(steps, beats) => {
+                let mut rate_string = String::new();
+                write!(rate_string, "{steps}/{beats}").ok();
+                return RateString(rate_string)
+            }
+            StepRate::Seq(Rate::Div(64, Frac::Zero)) => "/64",
+            StepRate::Seq(Rate::Div(32, Frac::Zero)) => "/32",
+            StepRate::Seq(Rate::Div(16, Frac::Zero)) => "/16",
+            StepRate::Seq(Rate::Div(8, Frac::Zero)) => "/8",
+            StepRate::Seq(Rate::Div(5, Frac::Zero)) => "/5",
+            StepRate::Seq(Rate::Div(4, Frac::Zero)) => "/4",
+            StepRate::Seq(Rate::Div(3, Frac::Zero)) => "/3",
+            StepRate::Note(3, 8) => "1t",
+            StepRate::Seq(Rate::Div(2, Frac::Zero)) => "/2",
+            StepRate::Note(2, 3) => "4.",
+            StepRate::Note(3, 4) => "2t",
+            StepRate::Seq(Rate::Unity) => "x1",
+            StepRate::Note(4, 3) => "8.",
+            StepRate::Note(3, 2) => "4t",
+            StepRate::Seq(Rate::Mult(2, Frac::Zero)) => "x2",
+            StepRate::Note(8, 3) => "16.",
+            StepRate::Seq(Rate::Mult(3, Frac::Zero)) => "x3",
+            StepRate::Seq(Rate::Mult(4, Frac::Zero)) => "x4",
+            StepRate::Seq(Rate::Mult(5, Frac::Zero)) => "x5",
+            StepRate::Seq(Rate::Mult(8, Frac::Zero)) => "x8",
+            StepRate::Seq(Rate::Mult(16, Frac::Zero)) => "x16",
+            StepRate::Seq(Rate::Mult(32, Frac::Zero)) => "x32",
+            StepRate::Seq(Rate::Mult(64, Frac::Zero)) => "x64",
+            StepRate::Seq(Rate::Mult(96, Frac::Zero)) => "x96",
+            _ => unreachable!(),
+        };
+
+        RateString(rate_string.into())
+    }
+}
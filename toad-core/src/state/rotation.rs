@@ -0,0 +1,39 @@
+use core::fmt;
+
+use super::{Updatable, MAX_LENGTH};
+
+/// How many steps a euclid pattern is pushed later, less than its length.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Rotation(pub u8);
+
+impl Rotation {
+    /// The same rotation, brought inside a pattern of `length` steps.
+    pub fn within(&self, length: u32) -> Self {
+        Self(self.0.min(length.saturating_sub(1) as u8))
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "r{}", self.0)
+    }
+}
+
+impl Updatable for Rotation {
+    fn next(&self) -> Option<Self> {
+        if self.0 as usize == MAX_LENGTH - 1 {
+            Option::None
+        } else {
+            Option::Some(Self(self.0 + 1))
+        }
+    }
+
+    fn prev(&self) -> Option<Self> {
+        if self.0 == 0 {
+            Option::None
+        } else {
+            Option::Some(Self(self.0 - 1))
+        }
+    }
+}
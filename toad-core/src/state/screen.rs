@@ -6,6 +6,7 @@ use super::Output;
 pub enum Screen {
     Home,
     Output(Output, OutputType),
+    Presets,
 }
 
 impl Screen {
@@ -4,6 +4,8 @@ use super::*;
 pub struct HomeScreenState {
     pub bpm: Bpm,
     pub sync: Sync,
+    pub ppqn: Ppqn,
+    pub swing: Swing,
     pub play_status: PlayStatus,
 }
 
@@ -11,35 +13,67 @@ pub struct HomeScreenState {
 pub struct OutputScreenState {
     pub output: Output,
     pub config: OutputConfig,
+    pub rate: StepRate,
+    pub rotation: Rotation,
+    pub swing: Option<Swing>,
+    pub edit_mode: EditMode,
     pub index: Option<usize>,
 }
 
+#[derive(Clone)]
+pub struct PresetsScreenState {
+    pub slot: Slot,
+    pub name: Name,
+    pub used: bool,
+}
+
 #[derive(Clone)]
 pub enum ScreenState {
     Home(HomeScreenState),
     Output(OutputScreenState),
+    Presets(PresetsScreenState),
 }
 
 impl ScreenState {
-    pub fn new_home(bpm: Bpm, sync: Sync, play_status: PlayStatus) -> ScreenState {
+    pub fn new_home(
+        bpm: Bpm,
+        sync: Sync,
+        ppqn: Ppqn,
+        swing: Swing,
+        play_status: PlayStatus,
+    ) -> ScreenState {
         ScreenState::Home(HomeScreenState {
             bpm,
             sync,
+            ppqn,
+            swing,
             play_status,
         })
     }
 
-    pub fn new_output(output: Output, config: OutputConfig, index: Option<usize>) -> ScreenState {
+    pub fn new_output(
+        output: Output,
+        config: OutputConfig,
+        rate: StepRate,
+        rotation: Rotation,
+        swing: Option<Swing>,
+        edit_mode: EditMode,
+        index: Option<usize>,
+    ) -> ScreenState {
         ScreenState::Output(OutputScreenState {
             output,
             config,
+            rate,
+            rotation,
+            swing,
+            edit_mode,
             index,
         })
     }
 
     pub fn index(&self) -> Option<usize> {
         match self {
-            ScreenState::Home(..) => Option::None,
+            ScreenState::Home(..) | ScreenState::Presets(..) => Option::None,
             ScreenState::Output(OutputScreenState { output, .. }) => {
                 Option::Some(usize::from(output))
             }
@@ -47,8 +81,8 @@ impl ScreenState {
     }
 
     pub fn set_index(&mut self, index: usize) {
-        if let ScreenState::Output(OutputScreenState { output, config, .. }) = self {
-            *self = ScreenState::new_output(*output, config.clone(), Some(index));
+        if let ScreenState::Output(OutputScreenState { index: current, .. }) = self {
+            *current = Some(index);
         }
     }
 }
@@ -61,6 +95,7 @@ impl From<ScreenState> for Screen {
                 let output_type = config.output_type();
                 Screen::Output(output, output_type)
             }
+            ScreenState::Presets(..) => Screen::Presets,
         }
     }
 }
@@ -73,6 +108,7 @@ impl From<&ScreenState> for Screen {
                 let output_type = config.output_type();
                 Screen::Output(*output, output_type)
             }
+            ScreenState::Presets(..) => Screen::Presets,
         }
     }
 }
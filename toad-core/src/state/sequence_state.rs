@@ -1,6 +1,6 @@
 use seq::{Density, Length};
 
-use super::{Output, StateChange};
+use super::{Output, Rotation, StateChange};
 
 #[derive(Clone)]
 pub struct SequenceState {
@@ -8,15 +8,17 @@ pub struct SequenceState {
     pub index: Option<usize>,
     pub length: Length,
     pub output: Output,
+    pub rotation: Rotation,
 }
 
 impl SequenceState {
-    pub fn new(output: Output, length: Length, density: Density) -> Self {
+    pub fn new(output: Output, length: Length, density: Density, rotation: Rotation) -> Self {
         Self {
             density,
             index: Option::None,
             length,
             output,
+            rotation,
         }
     }
 }
@@ -28,6 +30,7 @@ impl From<SequenceState> for StateChange {
             index: val.index,
             length: val.length,
             output: val.output,
+            rotation: val.rotation,
         })
     }
 }
@@ -1,24 +1,20 @@
 use core::fmt;
 
-use defmt::Format;
+use super::{Updatable, PRESETS};
 
-use super::Updatable;
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Slot(pub usize);
 
-pub const MIN_BPM: u32 = 1;
-pub const MAX_BPM: u32 = 300;
-
-#[derive(Clone, Copy, Format)]
-pub struct Bpm(pub u32);
-
-impl fmt::Display for Bpm {
+impl fmt::Display for Slot {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "P{}", self.0 + 1)
     }
 }
 
-impl Updatable for Bpm {
+impl Updatable for Slot {
     fn next(&self) -> Option<Self> {
-        if self.0 == MAX_BPM {
+        if self.0 == PRESETS - 1 {
             Option::None
         } else {
             Option::Some(Self(self.0 + 1))
@@ -26,7 +22,7 @@ impl Updatable for Bpm {
     }
 
     fn prev(&self) -> Option<Self> {
-        if self.0 == MIN_BPM {
+        if self.0 == 0 {
             Option::None
         } else {
             Option::Some(Self(self.0 - 1))
@@ -0,0 +1,652 @@
+use embassy_time::Instant;
+use heapless::Vec;
+use seq::{Frac, OutputType, Rate};
+
+use super::{rate::toggle_ratio, *};
+
+#[derive(Clone)]
+pub struct State {
+    pub bpm: Bpm,
+    pub bpm_sync: Option<BpmSync>,
+    pub edit_mode: EditMode,
+    pub acceleration: Acceleration,
+    pub tap_settings: TapSettings,
+    pub sync: Sync,
+    pub ppqn: Ppqn,
+    pub swing: Swing,
+    pub play_status: PlayStatus,
+    pub current_element: Element,
+    pub current_screen: Screen,
+    pub outputs: Vec<OutputConfig, 4>,
+    /// Each output's rate, of which `outputs` only hold what `Seq` plays.
+    pub rates: [StepRate; 4],
+    pub rotations: [Rotation; 4],
+    /// Outputs without a swing of their own follow `swing`.
+    pub swings: [Option<Swing>; 4],
+    pub presets: Vec<Option<Preset>, PRESETS>,
+    pub preset_slot: Slot,
+    /// Saved along with the preset, and taken from it when one is loaded.
+    pub preset_name: Name,
+    /// The character of `preset_name` being edited.
+    pub name_char: usize,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        let mut outputs = Vec::new();
+        outputs.push(OutputConfig::new()).ok();
+        outputs.push(OutputConfig::new()).ok();
+        outputs.push(OutputConfig::new()).ok();
+        outputs.push(OutputConfig::new()).ok();
+
+        Self::new(outputs)
+    }
+}
+
+impl State {
+    pub fn new(outputs: Vec<OutputConfig, 4>) -> Self {
+        let mut presets = Vec::new();
+        presets.resize(PRESETS, Option::None).ok();
+
+        let rates = core::array::from_fn(|idx| {
+            StepRate::Seq(outputs.get(idx).map_or(Rate::Unity, |config| config.rate()))
+        });
+
+        Self {
+            bpm: Bpm::from_whole(120),
+            bpm_sync: Option::None,
+            edit_mode: EditMode::Normal,
+            acceleration: Acceleration::default(),
+            tap_settings: TapSettings::default(),
+            sync: Sync::Int,
+            ppqn: Ppqn::default(),
+            swing: Swing::default(),
+            play_status: PlayStatus::Playing,
+            current_element: Element::Bpm,
+            current_screen: Screen::Home,
+            outputs,
+            rates,
+            rotations: [Rotation::default(); 4],
+            swings: [Option::None; 4],
+            presets,
+            preset_slot: Slot(0),
+            preset_name: Name::default(),
+            name_char: 0,
+        }
+    }
+
+    /// What the module powers up with before anything is loaded: output A a
+    /// euclid at 16 steps a beat, the rest gates.
+    pub fn initial() -> Self {
+        let mut outputs = Vec::new();
+
+        let mut gate_a_config = OutputConfig::new();
+        gate_a_config.set_output_type(OutputType::Euclid);
+        gate_a_config.set_rate(Rate::Mult(16, Frac::Zero));
+        outputs.push(gate_a_config).ok();
+
+        let gate_b_config = OutputConfig::new();
+        outputs.push(gate_b_config).ok();
+
+        let gate_c_config = OutputConfig::new();
+        outputs.push(gate_c_config).ok();
+
+        let gate_d_config = OutputConfig::new();
+        outputs.push(gate_d_config).ok();
+
+        Self::new(outputs)
+    }
+
+    pub fn preset(&self) -> Preset {
+        Preset {
+            name: self.preset_name,
+            bpm: self.bpm,
+            sync: self.sync,
+            ppqn: self.ppqn,
+            swing: self.swing,
+            outputs: self.outputs.clone(),
+            rates: self.rates,
+            rotations: self.rotations,
+            swings: self.swings,
+        }
+    }
+
+    pub fn apply_preset(&mut self, preset: &Preset) {
+        self.preset_name = preset.name;
+        self.bpm = preset.bpm;
+        self.bpm_sync = Option::None;
+        self.sync = preset.sync;
+        self.ppqn = preset.ppqn;
+        self.swing = preset.swing;
+        self.outputs = preset.outputs.clone();
+        self.rates = preset.rates;
+        self.rotations = preset.rotations;
+        self.swings = preset.swings;
+    }
+
+    pub fn handle_command(&mut self, command: Command) -> Option<StateChange> {
+        let current = &mut self.current_element.clone();
+
+        match command {
+            Command::EncoderRight(at) => {
+                let steps = self.steps(at, true);
+                current
+                    .next(self, steps)
+                    .map(|state_change| match state_change {
+                        StateChange::OutputType(ref screen_state) => {
+                            self.current_screen = screen_state.into();
+                            state_change
+                        }
+                        _ => state_change,
+                    })
+            }
+            Command::EncoderLeft(at) => {
+                let steps = self.steps(at, false);
+                current
+                    .prev(self, steps)
+                    .map(|state_change| match state_change {
+                        StateChange::OutputType(ref screen_state) => {
+                            self.current_screen = screen_state.into();
+                            state_change
+                        }
+                        _ => state_change,
+                    })
+            }
+            Command::EncoderPress => Some(match self.current_element {
+                Element::Load => self.load_preset(),
+                Element::Save => self.save_preset(),
+                _ => self.next_element(),
+            }),
+            Command::PagePress => Some(self.next_screen()),
+            Command::PlayPress => Some(self.toggle_play()),
+            Command::BpmPress(at) => self.bpm_sync(at.as_micros()),
+            Command::LongPress(Button::Play) => Some(self.stop()),
+            Command::LongPress(Button::Encoder) => Some(self.cycle_edit_mode()),
+            Command::DoublePress(Button::Encoder) => self.toggle_ratio(),
+            Command::LongPress(_) | Command::DoublePress(_) | Command::Chord(..) => None,
+        }
+    }
+
+    fn next_screen(&mut self) -> StateChange {
+        self.current_screen = match self.current_screen {
+            Screen::Home => Screen::Output(
+                Output::A,
+                self.outputs[usize::from(Output::A)].output_type(),
+            ),
+            Screen::Output(Output::A, _) => Screen::Output(
+                Output::B,
+                self.outputs[usize::from(Output::B)].output_type(),
+            ),
+            Screen::Output(Output::B, _) => Screen::Output(
+                Output::C,
+                self.outputs[usize::from(Output::C)].output_type(),
+            ),
+            Screen::Output(Output::C, _) => Screen::Output(
+                Output::D,
+                self.outputs[usize::from(Output::D)].output_type(),
+            ),
+            Screen::Output(Output::D, _) => Screen::Presets,
+            Screen::Presets => Screen::Home,
+        };
+        self.edit_mode = EditMode::Normal;
+        self.current_element = match self.current_screen {
+            Screen::Home => Element::Bpm,
+            Screen::Output(..) => Element::Rate,
+            Screen::Presets => Element::Slot,
+        };
+
+        StateChange::NextScreen(self.to_screen_state())
+    }
+
+    fn next_element(&mut self) -> StateChange {
+        let prev_element = self.current_element.clone();
+
+        self.current_element = match self.current_element {
+            Element::Bpm => Element::Sync,
+            Element::Sync => Element::Ppqn,
+            Element::Ppqn => Element::Swing,
+            Element::Swing => match self.current_screen {
+                Screen::Output(..) => Element::OutputType,
+                Screen::Home | Screen::Presets => Element::Bpm,
+            },
+            Element::Rate => match &self.current_screen {
+                Screen::Home | Screen::Presets => unreachable!(),
+                Screen::Output(output, output_type) => {
+                    match (self.rates[usize::from(*output)], output_type) {
+                        (StepRate::Ratio(..), _) => Element::Beats,
+                        (_, OutputType::Gate) => Element::Prob,
+                        (_, OutputType::Euclid) => Element::Length,
+                    }
+                }
+            },
+            Element::Beats => match &self.current_screen {
+                Screen::Home | Screen::Presets => unreachable!(),
+                Screen::Output(_, output_type) => match output_type {
+                    OutputType::Gate => Element::Prob,
+                    OutputType::Euclid => Element::Length,
+                },
+            },
+            Element::Length => Element::Density,
+            Element::Density => Element::Rotation,
+            Element::Rotation => Element::Swing,
+            Element::Prob => Element::Pwm,
+            Element::Pwm => Element::Swing,
+            Element::OutputType => Element::Rate,
+            Element::Slot => {
+                self.name_char = 0;
+                Element::Name
+            }
+            Element::Name if self.name_char < NAME_LENGTH - 1 => {
+                self.name_char += 1;
+                return StateChange::Name(self.preset_name, self.name_char)
+            }
+            Element::Name => Element::Load,
+            // Pressed, these act instead of moving on.
+            Element::Load | Element::Save => unreachable!(),
+        };
+
+        StateChange::NextElement(
+            self.current_screen,
+            prev_element,
+            self.current_element.clone(),
+        )
+    }
+
+    fn to_screen_state(&self) -> ScreenState {
+        match self.current_screen {
+            Screen::Home => ScreenState::Home(HomeScreenState {
+                bpm: self.bpm,
+                sync: self.sync,
+                ppqn: self.ppqn,
+                swing: self.swing,
+                play_status: self.play_status,
+            }),
+            Screen::Output(output, _) => {
+                let config = self.outputs[usize::from(output)].clone();
+                let idx = usize::from(output);
+                ScreenState::new_output(
+                    output,
+                    config,
+                    self.rates[idx],
+                    self.rotations[idx],
+                    self.swings[idx],
+                    self.edit_mode,
+                    Option::None,
+                )
+            }
+            Screen::Presets => ScreenState::Presets(self.presets_screen_state()),
+        }
+    }
+
+    fn presets_screen_state(&self) -> PresetsScreenState {
+        PresetsScreenState {
+            slot: self.preset_slot,
+            name: self.preset_name,
+            used: self.preset_used(),
+        }
+    }
+
+    /// Selects `slot`, taking its name if it has a preset in it. An empty
+    /// slot keeps the current name, to save under.
+    pub(super) fn select_preset(&mut self, slot: Slot) -> StateChange {
+        self.preset_slot = slot;
+        if let Option::Some(preset) = &self.presets[slot.0] {
+            self.preset_name = preset.name;
+        }
+
+        StateChange::Slot(self.presets_screen_state())
+    }
+
+    pub(super) fn rename_preset(&mut self, name: Name) -> StateChange {
+        self.preset_name = name;
+
+        StateChange::Name(name, self.name_char)
+    }
+
+    pub(super) fn pick_action(&mut self, element: Element) -> StateChange {
+        let prev_element = core::mem::replace(&mut self.current_element, element.clone());
+
+        StateChange::NextElement(self.current_screen, prev_element, element)
+    }
+
+    /// Loads the slot, if there's anything in it, and goes back to the slot.
+    fn load_preset(&mut self) -> StateChange {
+        self.current_element = Element::Slot;
+        match self.presets[self.preset_slot.0].clone() {
+            Option::Some(preset) => {
+                self.apply_preset(&preset);
+                StateChange::Preset(preset)
+            }
+            Option::None => StateChange::Slot(self.presets_screen_state()),
+        }
+    }
+
+    /// Saves to the slot and goes back to it.
+    fn save_preset(&mut self) -> StateChange {
+        self.current_element = Element::Slot;
+        self.presets[self.preset_slot.0] = Option::Some(self.preset());
+
+        StateChange::Slot(self.presets_screen_state())
+    }
+
+    fn preset_used(&self) -> bool {
+        self.presets[self.preset_slot.0].is_some()
+    }
+
+    fn toggle_play(&mut self) -> StateChange {
+        self.play_status = match self.play_status {
+            PlayStatus::Playing => PlayStatus::Paused,
+            PlayStatus::Paused | PlayStatus::Stopped => PlayStatus::Playing,
+        };
+
+        StateChange::PlayStatus(self.current_screen, self.play_status)
+    }
+
+    fn steps(&mut self, at: Instant, right: bool) -> u32 {
+        let steps = self.acceleration.steps(at.as_micros(), right);
+
+        self.edit_mode.steps(steps)
+    }
+
+    fn cycle_edit_mode(&mut self) -> StateChange {
+        self.edit_mode = self.edit_mode.cycle();
+
+        StateChange::EditMode(self.current_screen, self.edit_mode)
+    }
+
+    /// Switches the output's rate between the table and a ratio, from the
+    /// rate element.
+    fn toggle_ratio(&mut self) -> Option<StateChange> {
+        let (Screen::Output(output, output_type), Element::Rate) =
+            (self.current_screen, &self.current_element)
+        else {
+            return Option::None
+        };
+        let idx = usize::from(output);
+        let rate = toggle_ratio(self.rates[idx]);
+        self.rates[idx] = rate;
+        self.outputs[idx].set_rate(rate.seq_rate());
+
+        Option::Some(StateChange::Rate(output, output_type, rate))
+    }
+
+    fn stop(&mut self) -> StateChange {
+        self.play_status = PlayStatus::Stopped;
+
+        StateChange::Stop(self.current_screen, self.preset())
+    }
+
+    /// Taps only set the internal clock's tempo. Following the external
+    /// clock, they're ignored and start counting afresh once it's left.
+    fn bpm_sync(&mut self, at: u64) -> Option<StateChange> {
+        if let Sync::Ext = self.sync {
+            self.bpm_sync = Option::None;
+            return Option::None
+        }
+
+        let tenths = match &mut self.bpm_sync {
+            Option::None => {
+                self.bpm_sync = Option::Some(BpmSync::new(self.tap_settings, at));
+                return Option::None
+            }
+            Option::Some(bpm_sync) => bpm_sync.pulse(at)?,
+        };
+        self.bpm = Bpm(tenths);
+
+        if self.tap_settings.realign {
+            Option::Some(StateChange::Downbeat(self.preset(), at))
+        } else {
+            Option::Some(StateChange::Bpm(self.bpm))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Turns the encoder right once, a second after `turn`.
+    fn right(state: &mut State, turn: u64) -> Option<StateChange> {
+        state.handle_command(Command::EncoderRight(Instant::from_micros(
+            turn * 1_000_000,
+        )))
+    }
+
+    fn press(state: &mut State, presses: usize) {
+        (0..presses).for_each(|_| {
+            state.handle_command(Command::EncoderPress);
+        });
+    }
+
+    fn page(state: &mut State, pages: usize) {
+        (0..pages).for_each(|_| {
+            state.handle_command(Command::PagePress);
+        });
+    }
+
+    #[test]
+    fn page_goes_round_every_screen() {
+        let mut state = State::default();
+        state.outputs[1].set_output_type(OutputType::Euclid);
+
+        page(&mut state, 1);
+        assert!(matches!(
+            state.current_screen,
+            Screen::Output(Output::A, OutputType::Gate)
+        ));
+        assert!(matches!(state.current_element, Element::Rate));
+        page(&mut state, 1);
+        assert!(matches!(
+            state.current_screen,
+            Screen::Output(Output::B, OutputType::Euclid)
+        ));
+        page(&mut state, 3);
+        assert!(matches!(state.current_screen, Screen::Presets));
+        assert!(matches!(state.current_element, Element::Slot));
+        page(&mut state, 1);
+        assert!(matches!(state.current_screen, Screen::Home));
+        assert!(matches!(state.current_element, Element::Bpm));
+    }
+
+    #[test]
+    fn encoder_press_goes_round_the_home_screen() {
+        let mut state = State::default();
+
+        let change = state.handle_command(Command::EncoderPress);
+        assert!(matches!(
+            change,
+            Option::Some(StateChange::NextElement(
+                Screen::Home,
+                Element::Bpm,
+                Element::Sync
+            ))
+        ));
+        press(&mut state, 3);
+        assert!(matches!(state.current_element, Element::Bpm));
+    }
+
+    #[test]
+    fn encoder_turns_the_selected_element() {
+        let mut state = State::default();
+
+        let change = right(&mut state, 1);
+        assert!(matches!(change, Option::Some(StateChange::Bpm(Bpm(1_210)))));
+        assert!(state.bpm == Bpm(1_210));
+    }
+
+    #[test]
+    fn play_pauses_resumes_and_stops() {
+        let mut state = State::default();
+
+        state.handle_command(Command::PlayPress);
+        assert!(matches!(state.play_status, PlayStatus::Paused));
+        state.handle_command(Command::PlayPress);
+        assert!(matches!(state.play_status, PlayStatus::Playing));
+        let change = state.handle_command(Command::LongPress(Button::Play));
+        assert!(matches!(
+            change,
+            Option::Some(StateChange::Stop(Screen::Home, _))
+        ));
+        assert!(matches!(state.play_status, PlayStatus::Stopped));
+    }
+
+    #[test]
+    fn long_press_cycles_the_edit_mode_until_the_page_changes() {
+        let mut state = State::default();
+
+        let change = state.handle_command(Command::LongPress(Button::Encoder));
+        assert!(matches!(
+            change,
+            Option::Some(StateChange::EditMode(Screen::Home, EditMode::Fine))
+        ));
+        page(&mut state, 1);
+        assert!(state.edit_mode == EditMode::Normal);
+    }
+
+    #[test]
+    fn double_press_toggles_a_ratio_only_on_the_rate() {
+        let mut state = State::default();
+
+        assert!(state
+            .handle_command(Command::DoublePress(Button::Encoder))
+            .is_none());
+        page(&mut state, 1);
+        let change = state.handle_command(Command::DoublePress(Button::Encoder));
+        assert!(matches!(
+            change,
+            Option::Some(StateChange::Rate(Output::A, _, StepRate::Ratio(..)))
+        ));
+        assert!(state.outputs[0].rate() == Rate::Unity);
+        press(&mut state, 1);
+        assert!(matches!(state.current_element, Element::Beats));
+    }
+
+    #[test]
+    fn saves_and_loads_a_preset() {
+        let mut state = State::default();
+        page(&mut state, 5);
+        // Past the name to save.
+        press(&mut state, 1 + NAME_LENGTH);
+        right(&mut state, 1);
+        assert!(matches!(state.current_element, Element::Save));
+        press(&mut state, 1);
+        assert!(state.presets[0].is_some());
+
+        state.bpm = Bpm(900);
+        press(&mut state, 1 + NAME_LENGTH);
+        let change = state.handle_command(Command::EncoderPress);
+        assert!(matches!(change, Option::Some(StateChange::Preset(_))));
+        assert!(state.bpm == Bpm(1_200));
+        assert!(matches!(state.current_element, Element::Slot));
+    }
+
+    // Slightly uneven taps with a slip: an extra tap mid-beat after the fifth
+    // at 120 BPM, and at 93 BPM the fourth missed and the eighth hit twice.
+    const BETWEEN_120: [u64; 13] = [
+        1_998_611, 2_492_943, 3_000_937, 3_509_329, 3_989_582, 4_209_076, 4_505_559, 4_991_084,
+        5_499_982, 6_007_096, 6_489_900, 7_004_627, 7_495_035,
+    ];
+    const MISSED_DOUBLE_93: [u64; 12] = [
+        1_989_228, 2_635_977, 3_292_531, 4_570_933, 5_221_691, 5_861_938, 6_522_183, 6_564_138,
+        7_151_224, 7_812_977, 8_443_666, 9_092_086,
+    ];
+
+    /// Taps the BPM button at each of `taps` and returns the tempo and the
+    /// last tap that moved it.
+    fn tap_all(settings: TapSettings, taps: &[u64]) -> (Bpm, Option<u64>) {
+        let mut state = State {
+            tap_settings: settings,
+            ..State::default()
+        };
+        let mut counted = Option::None;
+        for at in taps {
+            let change = state.handle_command(Command::BpmPress(Instant::from_micros(*at)));
+            if change.is_some() {
+                counted = Option::Some(*at);
+            }
+        }
+
+        (state.bpm, counted)
+    }
+
+    /// The tempo with every way of averaging and rounding.
+    fn tap_tempos(taps: &[u64]) -> [u32; 4] {
+        let tenths = TapSettings {
+            rounding: Rounding::Tenths,
+            ..TapSettings::DEFAULT
+        };
+        let weighted = TapSettings {
+            averaging: Averaging::Weighted,
+            ..TapSettings::DEFAULT
+        };
+        let weighted_tenths = TapSettings {
+            averaging: Averaging::Weighted,
+            ..tenths
+        };
+
+        [TapSettings::DEFAULT, tenths, weighted, weighted_tenths].map(|settings| {
+            let (bpm, counted) = tap_all(settings, taps);
+            assert_eq!(counted, taps.last().copied());
+            bpm.0
+        })
+    }
+
+    #[test]
+    fn tapped_tempo_rides_out_an_extra_tap() {
+        assert_eq!(tap_tempos(&BETWEEN_120), [1_200, 1_203, 1_200, 1_203]);
+    }
+
+    #[test]
+    fn tapped_tempo_rides_out_missed_and_doubled_taps() {
+        assert_eq!(tap_tempos(&MISSED_DOUBLE_93), [930, 931, 930, 931]);
+    }
+
+    #[test]
+    fn tapped_tempo_realigns_only_when_set_to() {
+        let mut state = State::default();
+        state.tap_settings.realign = false;
+        let tap = |state: &mut State, at| {
+            state.handle_command(Command::BpmPress(Instant::from_micros(at)))
+        };
+
+        assert!(tap(&mut state, 1_000_000).is_none());
+        assert!(matches!(
+            tap(&mut state, 1_500_000),
+            Option::Some(StateChange::Bpm(Bpm(1_200)))
+        ));
+
+        state.tap_settings.realign = true;
+        state.bpm_sync = Option::None;
+        tap(&mut state, 3_000_000);
+        assert!(matches!(
+            tap(&mut state, 4_000_000),
+            Option::Some(StateChange::Downbeat(_, 4_000_000))
+        ));
+        assert!(state.bpm == Bpm(600));
+    }
+
+    #[test]
+    fn ignores_taps_on_the_external_clock() {
+        let mut state = State {
+            sync: Sync::Ext,
+            ..State::default()
+        };
+        let bpm = state.bpm;
+
+        for at in [1_000_000, 1_500_000, 2_000_000] {
+            let change = state.handle_command(Command::BpmPress(Instant::from_micros(at)));
+            assert!(change.is_none());
+        }
+        assert!(state.bpm == bpm);
+        assert!(state.bpm_sync.is_none());
+    }
+
+    #[test]
+    fn ignores_chords() {
+        let mut state = State::default();
+
+        assert!(state
+            .handle_command(Command::Chord(Button::Page, Button::Play))
+            .is_none());
+    }
+}
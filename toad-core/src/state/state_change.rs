@@ -1,32 +1,47 @@
-use seq::{OutputType, Prob, Pwm, Rate, Seq};
+use seq::{OutputType, Prob, Pwm};
 
 use super::*;
+use crate::sequencer::Sequencer;
 
 pub enum StateChange {
     Bpm(Bpm),
+    EditMode(Screen, EditMode),
     Sync(Sync),
-    Rate(Output, OutputType, Rate),
+    Ppqn(Ppqn),
+    /// `None` on an output's screen follows the global swing.
+    Swing(Screen, Option<Swing>),
+    Lock(Lock),
+    Rate(Output, OutputType, StepRate),
     Pwm(Output, Pwm),
     Prob(Output, Prob),
     Sequence(SequenceState),
     OutputType(ScreenState),
     PlayStatus(Screen, PlayStatus),
+    Stop(Screen, Preset),
+    /// A tapped tempo with the sequences starting over from the tap, in
+    /// microseconds.
+    Downbeat(Preset, u64),
     NextScreen(ScreenState),
     NextElement(Screen, Element, Element),
     Index(Output, usize),
+    Slot(PresetsScreenState),
+    /// The preset's name, and the character being edited.
+    Name(Name, usize),
+    Preset(Preset),
     Frame,
 }
 
 impl StateChange {
-    pub fn update_seq(&self, seq: &mut Seq) {
+    pub fn update_seq(&self, seq: &mut Sequencer) {
         match self {
-            StateChange::Bpm(bpm) => seq.set_bpm(bpm.0),
+            StateChange::Bpm(bpm) => seq.set_bpm(bpm.whole()),
             StateChange::Sequence(SequenceState {
                 output,
                 length,
                 density,
+                rotation,
                 ..
-            }) => seq.set_sequence(output.into(), *length, *density),
+            }) => seq.set_sequence(output.into(), *length, *density, *rotation),
             StateChange::OutputType(ScreenState::Output(OutputScreenState {
                 output,
                 ref config,
@@ -34,23 +49,33 @@ impl StateChange {
             })) => {
                 seq.set_output_type(output.into(), config.output_type());
             }
-            StateChange::PlayStatus(_, play_status) => match play_status {
-                PlayStatus::Playing => { /* TODO: pause */ }
-                PlayStatus::Paused => { /* TODO: reset then play */ }
-            },
+            StateChange::Stop(_, preset) | StateChange::Downbeat(preset, _) => seq.restart(preset),
             StateChange::Prob(output, prob) => seq.set_prob(output.into(), *prob),
             StateChange::Pwm(output, pwm) => seq.set_pwm(output.into(), *pwm),
             StateChange::Rate(output, _, rate) => seq.set_rate(output.into(), *rate),
-            StateChange::Frame
+            StateChange::Preset(preset) => seq.set_preset(preset),
+            StateChange::Swing(Screen::Output(output, _), swing) => {
+                seq.set_output_swing(output.into(), *swing)
+            }
+            StateChange::Swing(_, swing) => {
+                seq.set_swing(swing.unwrap_or_default());
+            }
+            StateChange::EditMode(..)
+            | StateChange::Frame
             | StateChange::Index(..)
             | StateChange::NextElement(..)
             | StateChange::NextScreen(..)
             | StateChange::OutputType(..)
+            | StateChange::Lock(_)
+            | StateChange::PlayStatus(..)
+            | StateChange::Ppqn(_)
+            | StateChange::Slot(..)
+            | StateChange::Name(..)
             | StateChange::Sync(_) => {}
         }
     }
 
-    pub fn update_index(self, seq: &Seq) -> Self {
+    pub fn update_index(self, seq: &Sequencer) -> Self {
         match self {
             StateChange::NextScreen(mut screen_state) => {
                 let index = seq.get_index(screen_state.index().unwrap_or(0));
@@ -75,9 +100,13 @@ impl StateChange {
 impl From<&StateChange> for Option<Screen> {
     fn from(val: &StateChange) -> Self {
         match val {
-            StateChange::Frame => Option::None,
-            StateChange::Bpm(_) | StateChange::Sync(_) => Option::Some(Screen::Home),
-            StateChange::PlayStatus(screen, _) => {
+            // A tapped tempo can come from any screen, and only shows on
+            // home.
+            StateChange::Frame | StateChange::Bpm(_) | StateChange::Downbeat(..) => Option::None,
+            StateChange::Sync(_) | StateChange::Ppqn(_) | StateChange::Lock(_) => {
+                Option::Some(Screen::Home)
+            }
+            StateChange::PlayStatus(screen, _) | StateChange::Stop(screen, _) => {
                 if let Screen::Home = screen {
                     Option::Some(Screen::Home)
                 } else {
@@ -100,12 +129,18 @@ impl From<&StateChange> for Option<Screen> {
                 }
                 _ => unreachable!(),
             },
-            StateChange::NextElement(screen, ..) => Option::Some(*screen),
+            StateChange::Slot(..) | StateChange::Name(..) | StateChange::Preset(_) => {
+                Option::Some(Screen::Presets)
+            }
+            StateChange::NextElement(screen, ..)
+            | StateChange::Swing(screen, _)
+            | StateChange::EditMode(screen, _) => Option::Some(*screen),
             StateChange::NextScreen(ref next_screen) => match next_screen {
                 ScreenState::Home(..) => Option::Some(Screen::Home),
                 ScreenState::Output(OutputScreenState { output, config, .. }) => {
                     Option::Some(Screen::Output(*output, config.output_type()))
                 }
+                ScreenState::Presets(..) => Option::Some(Screen::Presets),
             },
         }
     }
@@ -0,0 +1,83 @@
+use core::fmt::{self, Write};
+
+use heapless::String;
+
+use super::Updatable;
+
+pub const MIN_SWING: u8 = 50;
+pub const MAX_SWING: u8 = 75;
+
+/// How much of each pair of steps the first one takes, in percent. 50 is
+/// straight, about 67 is a triplet shuffle.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Swing(pub u8);
+
+impl Default for Swing {
+    fn default() -> Self {
+        Self(MIN_SWING)
+    }
+}
+
+impl Swing {
+    pub fn is_straight(&self) -> bool {
+        self.0 == MIN_SWING
+    }
+}
+
+impl fmt::Display for Swing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+impl Updatable for Swing {
+    fn next(&self) -> Option<Self> {
+        if self.0 == MAX_SWING {
+            Option::None
+        } else {
+            Option::Some(Self(self.0 + 1))
+        }
+    }
+
+    fn prev(&self) -> Option<Self> {
+        if self.0 == MIN_SWING {
+            Option::None
+        } else {
+            Option::Some(Self(self.0 - 1))
+        }
+    }
+}
+
+/// An output's own swing, or `None` to follow the global one, which comes
+/// before the straightest of its own.
+impl Updatable for Option<Swing> {
+    fn next(&self) -> Option<Self> {
+        match self {
+            Option::None => Option::Some(Option::Some(Swing::default())),
+            Option::Some(swing) => swing.next().map(Option::Some),
+        }
+    }
+
+    fn prev(&self) -> Option<Self> {
+        match self {
+            Option::None => Option::None,
+            Option::Some(swing) if swing.is_straight() => Option::Some(Option::None),
+            Option::Some(swing) => swing.prev().map(Option::Some),
+        }
+    }
+}
+
+pub struct SwingString(pub String<3>);
+
+impl From<Option<Swing>> for SwingString {
+    fn from(val: Option<Swing>) -> Self {
+        let mut swing_string = String::new();
+        match val {
+            Option::Some(swing) => write!(swing_string, "{swing}").unwrap(),
+            Option::None => swing_string.push_str("Glb").unwrap(),
+        };
+
+        SwingString(swing_string)
+    }
+}
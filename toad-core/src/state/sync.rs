@@ -1,10 +1,9 @@
 use core::fmt;
 
-use defmt::Format;
-
 use super::Updatable;
 
-#[derive(Clone, Copy, Format)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Sync {
     Int,
     Ext,
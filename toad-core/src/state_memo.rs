@@ -0,0 +1,43 @@
+use crate::state::{Bpm, PlayStatus, Ppqn, Screen, State, StateChange, Sync};
+
+pub struct StateMemo {
+    pub bpm: Bpm,
+    pub current_screen: Screen,
+    pub sync: Sync,
+    pub ppqn: Ppqn,
+    pub play_status: PlayStatus,
+}
+
+impl StateMemo {
+    pub fn new(state: &State) -> Self {
+        Self {
+            bpm: state.bpm,
+            current_screen: state.current_screen,
+            sync: state.sync,
+            ppqn: state.ppqn,
+            play_status: state.play_status,
+        }
+    }
+
+    pub fn update(&mut self, state_change: &StateChange) {
+        match state_change {
+            StateChange::Bpm(bpm) => self.bpm = *bpm,
+            StateChange::Downbeat(preset, _) => self.bpm = preset.bpm,
+            StateChange::NextScreen(next_screen) => self.current_screen = next_screen.into(),
+            StateChange::OutputType(screen_state) => self.current_screen = screen_state.into(),
+            StateChange::Sync(sync) => self.sync = *sync,
+            StateChange::Ppqn(ppqn) => self.ppqn = *ppqn,
+            StateChange::PlayStatus(_, play_status) => self.play_status = *play_status,
+            StateChange::Stop(_, preset) => {
+                self.bpm = preset.bpm;
+                self.play_status = PlayStatus::Stopped;
+            }
+            StateChange::Preset(preset) => {
+                self.bpm = preset.bpm;
+                self.sync = preset.sync;
+                self.ppqn = preset.ppqn;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub use self::store::{Store, SECTORS};
+
+mod codec;
+mod crc;
+#[cfg(test)]
+mod fake_flash;
+mod journal;
+mod store;
@@ -0,0 +1,504 @@
+//! Presets are stored as little-endian records:
+//!
+//! | offset   | size | field                                      |
+//! |----------|------|--------------------------------------------|
+//! | 0        | 4    | magic, `ToAd`                              |
+//! | 4        | 1    | format version                             |
+//! | 5        | 1    | header size, including the fields above    |
+//! | 6        | 1    | output size                                |
+//! | 7        | 1    | output count                               |
+//! | 8        | 2    | bpm, whole                                 |
+//! | 10       | 1    | sync, `0` int `1` ext                      |
+//! | 11       | 1    | ppqn                                       |
+//! | 12       | 1    | bpm, tenths                                |
+//! | 13       | 1    | swing, percent                             |
+//! | 14       | 6    | name, ASCII, blank padded                  |
+//! | header   | *    | output count times output size             |
+//! | *        | 4    | CRC-32 of every byte before it             |
+//!
+//! Each output is, one byte apiece: output type (`0` gate `1` euclid), rate
+//! kind (`0` unity `1` div `2` mult `3` ratio `4` note), rate value, rate
+//! frac, pwm, prob, length, density, rotation and swing percent, `0` to
+//! follow the global swing. A ratio or a note keeps its steps as the value
+//! and its beats in place of the frac. Enums are stored as their position in
+//! the tables below, never as a discriminant.
+//!
+//! Fields are only ever appended, to the header or to an output, so a
+//! decoder reads what it knows and falls back to defaults for fields an
+//! older record doesn't have. Anything that changes the meaning of an
+//! existing field bumps `VERSION` and gets a migration in `decode`. Records
+//! from a newer firmware are rejected rather than guessed at.
+//!
+//! Version 0 predates all of this; it has magic `TOAD`, no version, sizes
+//! or CRC, and sat alone in the last sector of flash. `decode_v0` reads it
+//! for `Store::load` to migrate once.
+
+use heapless::Vec;
+use seq::{Density, Frac, Length, OutputConfig, OutputType, Prob, Pwm, Rate};
+
+use super::crc::crc32;
+use crate::state::{
+    Bpm, Name, Ppqn, Preset, Rotation, StepRate, Swing, Sync, MAX_BPM, MAX_LENGTH, MAX_RATIO,
+    MAX_SWING, MIN_BPM, MIN_SWING, RATES,
+};
+
+const MAGIC: [u8; 4] = *b"ToAd";
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 20;
+const OUTPUT_SIZE: usize = 10;
+const OUTPUTS: usize = 4;
+const CRC_SIZE: usize = 4;
+
+const V0_MAGIC: [u8; 4] = *b"TOAD";
+const V0_OUTPUTS_START: usize = 7;
+const V0_OUTPUT_SIZE: usize = 8;
+
+/// Room for records to grow without moving anything in flash.
+pub const RECORD_SIZE: usize = 96;
+
+const FRACS: [Frac; 1] = [Frac::Zero];
+
+const PPQNS: [Ppqn; 6] = [Ppqn::P1, Ppqn::P2, Ppqn::P4, Ppqn::P8, Ppqn::P24, Ppqn::P48];
+
+const PROBS: [Prob; 10] = [
+    Prob::P10,
+    Prob::P20,
+    Prob::P30,
+    Prob::P40,
+    Prob::P50,
+    Prob::P60,
+    Prob::P70,
+    Prob::P80,
+    Prob::P90,
+    Prob::P100,
+];
+
+const PWMS: [Pwm; 10] = [
+    Pwm::Pew,
+    Pwm::P10,
+    Pwm::P20,
+    Pwm::P30,
+    Pwm::P40,
+    Pwm::P50,
+    Pwm::P60,
+    Pwm::P70,
+    Pwm::P80,
+    Pwm::P90,
+];
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    Magic,
+    Version(u8),
+    Size,
+    Crc,
+    Field,
+}
+
+pub fn encode(preset: &Preset) -> [u8; RECORD_SIZE] {
+    let mut bytes = [0; RECORD_SIZE];
+    bytes[0..4].copy_from_slice(&MAGIC);
+    bytes[4] = VERSION;
+    bytes[5] = HEADER_SIZE as u8;
+    bytes[6] = OUTPUT_SIZE as u8;
+    bytes[7] = preset.outputs.len() as u8;
+    bytes[8..10].copy_from_slice(&((preset.bpm.0 / 10) as u16).to_le_bytes());
+    bytes[10] = encode_sync(preset.sync);
+    bytes[11] = position(&PPQNS, &preset.ppqn);
+    bytes[12] = (preset.bpm.0 % 10) as u8;
+    bytes[13] = preset.swing.0;
+    bytes[14..20].copy_from_slice(&preset.name.0);
+
+    let chunks = bytes[HEADER_SIZE..].chunks_exact_mut(OUTPUT_SIZE);
+    let outputs = preset
+        .outputs
+        .iter()
+        .zip(preset.rates)
+        .zip(preset.rotations)
+        .zip(preset.swings);
+    for ((((config, rate), rotation), swing), chunk) in outputs.zip(chunks) {
+        encode_output(config, rate, rotation, swing, chunk);
+    }
+
+    let len = HEADER_SIZE + preset.outputs.len() * OUTPUT_SIZE;
+    let crc = crc32(&bytes[..len]);
+    bytes[len..len + CRC_SIZE].copy_from_slice(&crc.to_le_bytes());
+
+    bytes
+}
+
+pub fn decode(bytes: &[u8; RECORD_SIZE]) -> Result<Preset, Error> {
+    if bytes[0..4] != MAGIC {
+        return Err(Error::Magic)
+    }
+
+    let version = bytes[4];
+    if version > VERSION {
+        return Err(Error::Version(version))
+    }
+
+    let header_size = bytes[5] as usize;
+    let output_size = bytes[6] as usize;
+    let output_count = bytes[7] as usize;
+    let len = header_size + output_count * output_size;
+    if header_size < HEADER_SIZE
+        || output_size < OUTPUT_SIZE
+        || output_count != OUTPUTS
+        || len + CRC_SIZE > RECORD_SIZE
+    {
+        return Err(Error::Size)
+    }
+
+    let crc = u32::from_le_bytes(bytes[len..len + CRC_SIZE].try_into().unwrap());
+    if crc != crc32(&bytes[..len]) {
+        return Err(Error::Crc)
+    }
+
+    let bpm = u16::from_le_bytes([bytes[8], bytes[9]]);
+    let name = Name::from_bytes(&bytes[14..20]).ok_or(Error::Field)?;
+    let outputs = bytes[header_size..len].chunks_exact(output_size);
+
+    decode_preset(
+        name,
+        bpm,
+        bytes[12],
+        bytes[10],
+        Option::Some(bytes[11]),
+        Option::Some(bytes[13]),
+        outputs,
+    )
+}
+
+/// A version 0 record, which has no CRC to check.
+pub fn decode_v0(bytes: &[u8; RECORD_SIZE]) -> Result<Preset, Error> {
+    if bytes[0..4] != V0_MAGIC {
+        return Err(Error::Magic)
+    }
+
+    let bpm = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let outputs_end = V0_OUTPUTS_START + OUTPUTS * V0_OUTPUT_SIZE;
+    let outputs = bytes[V0_OUTPUTS_START..outputs_end].chunks_exact(V0_OUTPUT_SIZE);
+
+    decode_preset(
+        Name::default(),
+        bpm,
+        0,
+        bytes[6],
+        Option::None,
+        Option::None,
+        outputs,
+    )
+}
+
+fn decode_preset<'a>(
+    name: Name,
+    bpm: u16,
+    tenths: u8,
+    sync: u8,
+    ppqn: Option<u8>,
+    swing: Option<u8>,
+    outputs: impl Iterator<Item = &'a [u8]>,
+) -> Result<Preset, Error> {
+    let bpm = Bpm(bpm as u32 * 10 + tenths as u32);
+    if tenths > 9 || !(MIN_BPM * 10..=MAX_BPM * 10).contains(&bpm.0) {
+        return Err(Error::Field)
+    }
+    let sync = decode_sync(sync).ok_or(Error::Field)?;
+    let ppqn = match ppqn {
+        Option::Some(byte) => *PPQNS.get(byte as usize).ok_or(Error::Field)?,
+        Option::None => Ppqn::default(),
+    };
+    let swing = decode_swing(swing).ok_or(Error::Field)?;
+
+    let mut configs = Vec::new();
+    let mut rates = [StepRate::Seq(Rate::Unity); OUTPUTS];
+    let mut rotations = [Rotation::default(); OUTPUTS];
+    let mut swings = [Option::None; OUTPUTS];
+    for (idx, output) in outputs.enumerate().take(OUTPUTS) {
+        let (config, rate, rotation, output_swing) = decode_output(output).ok_or(Error::Field)?;
+        configs.push(config).ok();
+        rates[idx] = rate;
+        rotations[idx] = rotation;
+        swings[idx] = output_swing;
+    }
+
+    Ok(Preset {
+        name,
+        bpm,
+        sync,
+        ppqn,
+        swing,
+        outputs: configs,
+        rates,
+        rotations,
+        swings,
+    })
+}
+
+fn encode_sync(sync: Sync) -> u8 {
+    match sync {
+        Sync::Int => 0,
+        Sync::Ext => 1,
+    }
+}
+
+fn decode_sync(byte: u8) -> Option<Sync> {
+    match byte {
+        0 => Option::Some(Sync::Int),
+        1 => Option::Some(Sync::Ext),
+        _ => Option::None,
+    }
+}
+
+fn encode_output(
+    config: &OutputConfig,
+    rate: StepRate,
+    rotation: Rotation,
+    swing: Option<Swing>,
+    bytes: &mut [u8],
+) {
+    bytes[0] = encode_output_type(config.output_type());
+    bytes[1..4].copy_from_slice(&encode_rate(rate));
+    bytes[4] = position(&PWMS, &config.pwm());
+    bytes[5] = position(&PROBS, &config.prob());
+    bytes[6] = config.length().0 as u8;
+    bytes[7] = config.density().0 as u8;
+    bytes[8] = rotation.0;
+    bytes[9] = swing.map_or(0, |swing| swing.0);
+}
+
+fn decode_output(bytes: &[u8]) -> Option<(OutputConfig, StepRate, Rotation, Option<Swing>)> {
+    let length = Length(bytes[6].into());
+    let density = Density(bytes[7].into());
+    let rotation = Rotation(bytes.get(8).copied().unwrap_or(0));
+    let swing = match bytes.get(9).copied() {
+        Option::None | Option::Some(0) => Option::None,
+        percent => Option::Some(decode_swing(percent)?),
+    };
+    if length.0 < 1
+        || length.0 as usize > MAX_LENGTH
+        || density.0 > length.0
+        || rotation.0 as u32 >= length.0 as u32
+    {
+        return Option::None
+    }
+
+    let rate = decode_rate(&bytes[1..4])?;
+    let mut config = OutputConfig::new();
+    config.set_output_type(decode_output_type(bytes[0])?);
+    config.set_rate(rate.seq_rate());
+    config.set_pwm(*PWMS.get(bytes[4] as usize)?);
+    config.set_prob(*PROBS.get(bytes[5] as usize)?);
+    config.set_sequence(length, density);
+
+    Option::Some((config, rate, rotation, swing))
+}
+
+fn decode_swing(byte: Option<u8>) -> Option<Swing> {
+    match byte {
+        Option::Some(percent) => {
+            Option::Some(Swing(percent)).filter(|_| (MIN_SWING..=MAX_SWING).contains(&percent))
+        }
+        Option::None => Option::Some(Swing::default()),
+    }
+}
+
+fn encode_output_type(output_type: OutputType) -> u8 {
+    match output_type {
+        OutputType::Gate => 0,
+        OutputType::Euclid => 1,
+    }
+}
+
+fn decode_output_type(byte: u8) -> Option<OutputType> {
+    match byte {
+        0 => Option::Some(OutputType::Gate),
+        1 => Option::Some(OutputType::Euclid),
+        _ => Option::None,
+    }
+}
+
+fn encode_rate(rate: StepRate) -> [u8; 3] {
+    match rate {
+        StepRate::Seq(Rate::Unity) => [0, 1, 0],
+        StepRate::Seq(Rate::Div(n, frac)) => [1, n as u8, position(&FRACS, &frac)],
+        StepRate::Seq(Rate::Mult(n, frac)) => [2, n as u8, position(&FRACS, &frac)],
+        StepRate::Ratio(steps, beats) => [3, steps as u8, beats as u8],
+        StepRate::Note(steps, beats) => [4, steps as u8, beats as u8],
+    }
+}
+
+fn decode_rate(bytes: &[u8]) -> Option<StepRate> {
+    if bytes[0] == 3 {
+        let (steps, beats) = (bytes[1] as u32, bytes[2] as u32);
+        let parts = 1..=MAX_RATIO;
+        return (parts.contains(&steps) && parts.contains(&beats))
+            .then_some(StepRate::Ratio(steps, beats))
+    }
+    if bytes[0] == 4 {
+        let note = StepRate::Note(bytes[1].into(), bytes[2].into());
+        return RATES.contains(&note).then_some(note)
+    }
+
+    let frac = *FRACS.get(bytes[2] as usize)?;
+    let rate = match bytes[0] {
+        0 => Rate::Unity,
+        1 => Rate::Div(bytes[1].into(), frac),
+        2 => Rate::Mult(bytes[1].into(), frac),
+        _ => return Option::None,
+    };
+
+    let rate = StepRate::Seq(rate);
+
+    RATES.contains(&rate).then_some(rate)
+}
+
+fn position<T: PartialEq>(table: &[T], value: &T) -> u8 {
+    table.iter().position(|v| v == value).unwrap() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::State;
+
+    // A gate at unity, pwm 50%, prob 100%, 16 steps of 4, as the first eight
+    // bytes of an output.
+    const GATE: [u8; 8] = [0, 0, 1, 0, 5, 9, 16, 4];
+
+    fn preset() -> Preset {
+        let mut preset = State::default().preset();
+        preset.name = Name(*b"TOAD-1");
+        preset.bpm = Bpm(1_234);
+        preset.sync = Sync::Ext;
+        preset.ppqn = Ppqn::P24;
+        preset.swing = Swing(60);
+        preset.outputs[0].set_output_type(OutputType::Euclid);
+        preset.outputs[0].set_sequence(Length(8), Density(3));
+        preset.rotations[0] = Rotation(5);
+        preset.rates[1] = StepRate::Ratio(3, 4);
+        preset.rates[2] = StepRate::Note(3, 2);
+        preset.swings[3] = Option::Some(Swing(55));
+        preset
+    }
+
+    /// `header` and every output as `output`, with the CRC after them.
+    fn record(header: &[u8], output: &[u8]) -> [u8; RECORD_SIZE] {
+        let mut bytes = [0; RECORD_SIZE];
+        bytes[..header.len()].copy_from_slice(header);
+        let outputs = &mut bytes[header.len()..][..OUTPUTS * output.len()];
+        outputs
+            .chunks_exact_mut(output.len())
+            .for_each(|chunk| chunk.copy_from_slice(output));
+        let len = header.len() + OUTPUTS * output.len();
+        let crc = crc32(&bytes[..len]);
+        bytes[len..len + CRC_SIZE].copy_from_slice(&crc.to_le_bytes());
+
+        bytes
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let bytes = encode(&preset());
+        let Ok(decoded) = decode(&bytes) else {
+            panic!("didn't decode")
+        };
+
+        assert!(decoded.name == Name(*b"TOAD-1"));
+        assert!(decoded.bpm == Bpm(1_234));
+        assert!(matches!(decoded.sync, Sync::Ext));
+        assert!(decoded.ppqn == Ppqn::P24);
+        assert!(decoded.swing == Swing(60));
+        assert!(decoded.outputs[0].output_type() == OutputType::Euclid);
+        assert!(decoded.outputs[0].length() == Length(8));
+        assert!(decoded.outputs[0].density() == Density(3));
+        assert!(decoded.rotations[0] == Rotation(5));
+        assert!(decoded.rates[1] == StepRate::Ratio(3, 4));
+        assert!(decoded.rates[2] == StepRate::Note(3, 2));
+        assert!(decoded.swings[3] == Option::Some(Swing(55)));
+        assert_eq!(encode(&decoded), bytes);
+    }
+
+    #[test]
+    fn rejects_a_bad_crc() {
+        let mut bytes = encode(&preset());
+        bytes[8] ^= 1;
+
+        assert!(matches!(decode(&bytes), Err(Error::Crc)));
+    }
+
+    #[test]
+    fn rejects_a_newer_version() {
+        let mut bytes = encode(&preset());
+        bytes[4] = VERSION + 1;
+
+        assert!(matches!(decode(&bytes), Err(Error::Version(v)) if v == VERSION + 1));
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        assert!(matches!(decode(&[0xFF; RECORD_SIZE]), Err(Error::Magic)));
+    }
+
+    #[test]
+    fn rejects_sizes_that_dont_fit() {
+        let mut bytes = encode(&preset());
+        bytes[7] = OUTPUTS as u8 - 1;
+
+        assert!(matches!(decode(&bytes), Err(Error::Size)));
+    }
+
+    #[test]
+    fn rejects_a_field_out_of_range() {
+        let header = encode(&preset());
+        let mut rotated = [0; OUTPUT_SIZE];
+        rotated[..8].copy_from_slice(&GATE);
+        // As far round as the length.
+        rotated[8] = 16;
+        let bytes = record(&header[..HEADER_SIZE], &rotated);
+
+        assert!(matches!(decode(&bytes), Err(Error::Field)));
+    }
+
+    #[test]
+    fn rejects_a_header_short_of_its_fields() {
+        let mut header = [0; HEADER_SIZE - 1];
+        header.copy_from_slice(&encode(&preset())[..HEADER_SIZE - 1]);
+        header[5] = HEADER_SIZE as u8 - 1;
+        let mut output = [0; OUTPUT_SIZE];
+        output[..8].copy_from_slice(&GATE);
+        let bytes = record(&header, &output);
+
+        assert!(matches!(decode(&bytes), Err(Error::Size)));
+    }
+
+    #[test]
+    fn reads_only_version_0_as_version_0() {
+        assert!(matches!(decode_v0(&encode(&preset())), Err(Error::Magic)));
+    }
+
+    #[test]
+    fn migrates_version_0() {
+        let mut bytes = [0; RECORD_SIZE];
+        bytes[..4].copy_from_slice(&V0_MAGIC);
+        bytes[4..6].copy_from_slice(&140u16.to_le_bytes());
+        bytes[6] = 1;
+        for output in bytes[V0_OUTPUTS_START..]
+            .chunks_exact_mut(V0_OUTPUT_SIZE)
+            .take(OUTPUTS)
+        {
+            output.copy_from_slice(&GATE);
+        }
+        // The second output divided by 4.
+        bytes[V0_OUTPUTS_START + V0_OUTPUT_SIZE + 1..][..2].copy_from_slice(&[1, 4]);
+        let Ok(preset) = decode_v0(&bytes) else {
+            panic!("didn't migrate")
+        };
+
+        assert!(preset.bpm == Bpm(1_400));
+        assert!(matches!(preset.sync, Sync::Ext));
+        assert!(preset.rates[1] == StepRate::Seq(Rate::Div(4, Frac::Zero)));
+        assert!(preset.outputs[1].pwm() == Pwm::P50);
+    }
+}
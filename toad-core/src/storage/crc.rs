@@ -0,0 +1,16 @@
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// CRC-32 (ISO-HDLC), the same one zlib and PNG use.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (POLYNOMIAL & mask);
+        }
+    }
+
+    !crc
+}
@@ -0,0 +1,103 @@
+//! Flash in memory for tests, which can lose power part way through a write
+//! or an erase. Like NOR flash, writing only ever clears bits.
+
+use embedded_storage::nor_flash::{
+    ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+
+use super::SECTORS;
+
+pub const ERASE_SIZE: usize = 256;
+pub const CAPACITY: usize = SECTORS * ERASE_SIZE;
+
+const ERASED: u8 = 0xFF;
+
+/// What's on the chip, which outlives any one power-up.
+pub fn chip() -> [u8; CAPACITY] {
+    [ERASED; CAPACITY]
+}
+
+/// The chip for one power-up.
+pub struct FakeFlash<'a> {
+    bytes: &'a mut [u8; CAPACITY],
+    // Bytes left to write or erase before the power goes.
+    power: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct PowerLost;
+
+impl NorFlashError for PowerLost {
+    fn kind(&self) -> NorFlashErrorKind {
+        NorFlashErrorKind::Other
+    }
+}
+
+impl<'a> FakeFlash<'a> {
+    pub fn new(bytes: &'a mut [u8; CAPACITY]) -> Self {
+        Self {
+            bytes,
+            power: Option::None,
+        }
+    }
+
+    /// Loses power after `bytes` more bytes are written or erased.
+    pub fn cut_after(bytes: &'a mut [u8; CAPACITY], power: usize) -> Self {
+        Self {
+            bytes,
+            power: Option::Some(power),
+        }
+    }
+
+    /// Sets each byte from `from` with `set`, until the power goes.
+    fn program(
+        &mut self,
+        from: u32,
+        len: usize,
+        mut set: impl FnMut(usize, &mut u8),
+    ) -> Result<(), PowerLost> {
+        let from = from as usize;
+        for (idx, byte) in self.bytes[from..from + len].iter_mut().enumerate() {
+            match &mut self.power {
+                Option::Some(0) => return Err(PowerLost),
+                Option::Some(power) => *power -= 1,
+                Option::None => {}
+            }
+            set(idx, byte);
+        }
+
+        Ok(())
+    }
+}
+
+impl ErrorType for FakeFlash<'_> {
+    type Error = PowerLost;
+}
+
+impl ReadNorFlash for FakeFlash<'_> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), PowerLost> {
+        let offset = offset as usize;
+        bytes.copy_from_slice(&self.bytes[offset..offset + bytes.len()]);
+
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        CAPACITY
+    }
+}
+
+impl NorFlash for FakeFlash<'_> {
+    const ERASE_SIZE: usize = ERASE_SIZE;
+    const WRITE_SIZE: usize = 1;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), PowerLost> {
+        self.program(from, (to - from) as usize, |_, byte| *byte = ERASED)
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), PowerLost> {
+        self.program(offset, bytes.len(), |idx, byte| *byte &= bytes[idx])
+    }
+}
@@ -0,0 +1,227 @@
+use embedded_storage::nor_flash::NorFlash;
+
+use super::{codec::RECORD_SIZE, crc::crc32};
+
+const SEQUENCE_SIZE: usize = 4;
+const CRC_SIZE: usize = 4;
+const ENTRY_SIZE: usize = SEQUENCE_SIZE + RECORD_SIZE + CRC_SIZE;
+const ERASED: u8 = 0xFF;
+
+/// An append-only log of records spread over a ring of erase sectors, so
+/// frequent saves wear every sector evenly instead of erasing one each time.
+///
+/// Each entry is a sequence number, the record and a CRC of both, written in
+/// that order. A write cut short by a power loss never gets a matching CRC,
+/// so it is ignored and the entry before it is still the latest. A sector is
+/// only erased once writing has moved past it and it holds nothing newer
+/// than the sector being left, which is why there must be at least two.
+pub struct Journal {
+    offset: u32,
+    sectors: usize,
+    sector: usize,
+    slot: usize,
+    sequence: u32,
+}
+
+impl Journal {
+    pub fn new(offset: u32, sectors: usize) -> Self {
+        debug_assert!(sectors >= 2);
+
+        Self {
+            offset,
+            sectors,
+            sector: 0,
+            slot: 0,
+            sequence: 0,
+        }
+    }
+
+    /// Scans the ring for the newest intact entry, copying its record into
+    /// `record`, and picks up writing after it.
+    pub fn mount<F: NorFlash>(&mut self, flash: &mut F, record: &mut [u8; RECORD_SIZE]) -> bool {
+        let mut latest: Option<(u32, usize, usize)> = Option::None;
+        let mut entry = [0; ENTRY_SIZE];
+
+        for sector in 0..self.sectors {
+            for slot in 0..Self::entries_per_sector::<F>() {
+                let entry_offset = self.entry_offset::<F>(sector, slot);
+                if flash.read(entry_offset, &mut entry).is_err() {
+                    continue
+                }
+                if entry.iter().all(|byte| *byte == ERASED) {
+                    break
+                }
+                let Option::Some(sequence) = Self::verify(&entry) else {
+                    continue
+                };
+                if latest.map_or(true, |(latest, ..)| sequence > latest) {
+                    record.copy_from_slice(&entry[SEQUENCE_SIZE..SEQUENCE_SIZE + RECORD_SIZE]);
+                    latest = Option::Some((sequence, sector, slot));
+                }
+            }
+        }
+
+        match latest {
+            Option::Some((sequence, sector, slot)) => {
+                self.sector = sector;
+                self.slot = slot + 1;
+                self.sequence = sequence.wrapping_add(1);
+                true
+            }
+            Option::None => {
+                self.sector = 0;
+                self.slot = 0;
+                self.sequence = 0;
+                false
+            }
+        }
+    }
+
+    pub fn append<F: NorFlash>(
+        &mut self,
+        flash: &mut F,
+        record: &[u8; RECORD_SIZE],
+    ) -> Result<(), F::Error> {
+        loop {
+            if self.slot == Self::entries_per_sector::<F>() {
+                self.sector = (self.sector + 1) % self.sectors;
+                self.slot = 0;
+                let sector_offset = self.entry_offset::<F>(self.sector, 0);
+                flash.erase(sector_offset, sector_offset + F::ERASE_SIZE as u32)?;
+            }
+            if self.is_erased(flash)? {
+                break
+            }
+            // Leftovers of an interrupted write can't be written over.
+            self.slot += 1;
+        }
+
+        let mut entry = [0; ENTRY_SIZE];
+        entry[..SEQUENCE_SIZE].copy_from_slice(&self.sequence.to_le_bytes());
+        entry[SEQUENCE_SIZE..SEQUENCE_SIZE + RECORD_SIZE].copy_from_slice(record);
+        let crc = crc32(&entry[..SEQUENCE_SIZE + RECORD_SIZE]);
+        entry[SEQUENCE_SIZE + RECORD_SIZE..].copy_from_slice(&crc.to_le_bytes());
+
+        flash.write(self.entry_offset::<F>(self.sector, self.slot), &entry)?;
+        self.slot += 1;
+        self.sequence = self.sequence.wrapping_add(1);
+
+        Ok(())
+    }
+
+    fn verify(entry: &[u8; ENTRY_SIZE]) -> Option<u32> {
+        let (data, crc) = entry.split_at(SEQUENCE_SIZE + RECORD_SIZE);
+        if u32::from_le_bytes(crc.try_into().unwrap()) != crc32(data) {
+            return Option::None
+        }
+
+        Option::Some(u32::from_le_bytes(
+            data[..SEQUENCE_SIZE].try_into().unwrap(),
+        ))
+    }
+
+    fn is_erased<F: NorFlash>(&self, flash: &mut F) -> Result<bool, F::Error> {
+        let mut entry = [0; ENTRY_SIZE];
+        flash.read(self.entry_offset::<F>(self.sector, self.slot), &mut entry)?;
+
+        Ok(entry.iter().all(|byte| *byte == ERASED))
+    }
+
+    fn entries_per_sector<F: NorFlash>() -> usize {
+        F::ERASE_SIZE / ENTRY_SIZE
+    }
+
+    fn entry_offset<F: NorFlash>(&self, sector: usize, slot: usize) -> u32 {
+        self.offset + (sector * F::ERASE_SIZE + slot * ENTRY_SIZE) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::fake_flash::{chip, FakeFlash, CAPACITY, ERASE_SIZE};
+
+    const SECTORS: usize = 3;
+
+    fn record(n: u8) -> [u8; RECORD_SIZE] {
+        [n; RECORD_SIZE]
+    }
+
+    /// What a journal finds on `chip` at power-up.
+    fn mounted(chip: &mut [u8; CAPACITY]) -> Option<[u8; RECORD_SIZE]> {
+        let mut record = [0; RECORD_SIZE];
+        Journal::new(0, SECTORS)
+            .mount(&mut FakeFlash::new(chip), &mut record)
+            .then_some(record)
+    }
+
+    #[test]
+    fn mounts_nothing_from_an_erased_chip() {
+        assert_eq!(mounted(&mut chip()), Option::None);
+    }
+
+    #[test]
+    fn mounts_the_last_record_after_going_round_the_ring() {
+        let mut chip = chip();
+        let mut journal = Journal::new(0, SECTORS);
+        for n in 0..50 {
+            journal
+                .append(&mut FakeFlash::new(&mut chip), &record(n))
+                .unwrap();
+
+            assert_eq!(mounted(&mut chip), Option::Some(record(n)));
+        }
+    }
+
+    #[test]
+    fn appends_after_what_it_mounted() {
+        let mut chip = chip();
+        Journal::new(0, SECTORS)
+            .append(&mut FakeFlash::new(&mut chip), &record(1))
+            .unwrap();
+
+        let mut journal = Journal::new(0, SECTORS);
+        journal.mount(&mut FakeFlash::new(&mut chip), &mut record(0));
+        journal
+            .append(&mut FakeFlash::new(&mut chip), &record(2))
+            .unwrap();
+
+        assert_eq!(mounted(&mut chip), Option::Some(record(2)));
+    }
+
+    /// Power goes at every byte of an append, including the erase of a
+    /// sector when it moves on to one, all the way round the ring.
+    #[test]
+    fn power_loss_leaves_the_last_record_or_the_new_one() {
+        let entries = SECTORS * (ERASE_SIZE / ENTRY_SIZE);
+        for saved in 1..2 * entries as u8 {
+            for power in 0..ENTRY_SIZE + ERASE_SIZE {
+                let mut chip = chip();
+                let mut journal = Journal::new(0, SECTORS);
+                for n in 1..=saved {
+                    journal
+                        .append(&mut FakeFlash::new(&mut chip), &record(n))
+                        .unwrap();
+                }
+
+                let mut flash = FakeFlash::cut_after(&mut chip, power);
+                let expected = match journal.append(&mut flash, &record(saved + 1)) {
+                    Ok(()) => saved + 1,
+                    Err(_) => saved,
+                };
+                assert_eq!(
+                    mounted(&mut chip),
+                    Option::Some(record(expected)),
+                    "{saved} saved, power lost after {power} bytes"
+                );
+
+                let mut journal = Journal::new(0, SECTORS);
+                journal.mount(&mut FakeFlash::new(&mut chip), &mut record(0));
+                journal
+                    .append(&mut FakeFlash::new(&mut chip), &record(0xAA))
+                    .unwrap();
+                assert_eq!(mounted(&mut chip), Option::Some(record(0xAA)));
+            }
+        }
+    }
+}
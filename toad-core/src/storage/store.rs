@@ -0,0 +1,283 @@
+use embedded_storage::nor_flash::NorFlash;
+
+use super::{
+    codec::{self, RECORD_SIZE},
+    journal::Journal,
+};
+use crate::state::{Preset, State, PRESETS};
+
+/// The current settings are saved on every change so they get a journal
+/// of their own over several sectors. Presets are only written when asked
+/// to but get a journal apiece too, so a preset is never erased before its
+/// new version is safely written.
+pub const SECTORS: usize = JOURNAL_SECTORS + PRESETS * PRESET_SECTORS;
+
+const JOURNAL_SECTORS: usize = 4;
+const PRESET_SECTORS: usize = 2;
+
+pub struct Store<F> {
+    flash: F,
+    // Where version 0 kept the settings, the last sector, which is now the
+    // second of the last preset's journal.
+    v0_offset: u32,
+    journal: Journal,
+    preset_journals: [Journal; PRESETS],
+    current: Option<[u8; RECORD_SIZE]>,
+    presets: [Option<[u8; RECORD_SIZE]>; PRESETS],
+}
+
+impl<F: NorFlash> Store<F> {
+    /// `offset` is the start of `SECTORS` erase sectors reserved for the
+    /// store, see `memory.x`.
+    pub fn new(flash: F, offset: u32) -> Self {
+        Self {
+            flash,
+            v0_offset: offset + ((SECTORS - 1) * F::ERASE_SIZE) as u32,
+            journal: Journal::new(offset, JOURNAL_SECTORS),
+            preset_journals: core::array::from_fn(|slot| {
+                let sector = JOURNAL_SECTORS + slot * PRESET_SECTORS;
+                Journal::new(offset + (sector * F::ERASE_SIZE) as u32, PRESET_SECTORS)
+            }),
+            current: Option::None,
+            presets: [Option::None; PRESETS],
+        }
+    }
+
+    /// Overwrites `state` with whatever valid records are in flash, leaving
+    /// the rest untouched. Settings saved by version 0 are taken the first
+    /// time, unless the journal has some already, and saved again from
+    /// there on the next `save`.
+    pub fn load(&mut self, state: &mut State) {
+        let v0 = self.take_v0();
+        let mut bytes = [0; RECORD_SIZE];
+        if self.journal.mount(&mut self.flash, &mut bytes) {
+            if let Ok(preset) = codec::decode(&bytes) {
+                state.apply_preset(&preset);
+                self.current = Option::Some(bytes);
+            }
+        } else if let Option::Some(preset) = v0 {
+            state.apply_preset(&preset);
+        }
+
+        for slot in 0..PRESETS {
+            if let Option::Some(preset) = self.read_preset(slot) {
+                state.presets[slot] = Option::Some(preset);
+            }
+        }
+    }
+
+    pub fn save(&mut self, state: &State) -> Result<(), F::Error> {
+        let bytes = codec::encode(&state.preset());
+        if self.current != Option::Some(bytes) {
+            self.journal.append(&mut self.flash, &bytes)?;
+            self.current = Option::Some(bytes);
+        }
+
+        for (slot, preset) in state.presets.iter().enumerate() {
+            if let Option::Some(preset) = preset {
+                self.write_preset(slot, preset)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads version 0's settings, erasing them so the last preset's journal
+    /// can have its sector back.
+    fn take_v0(&mut self) -> Option<Preset> {
+        let mut bytes = [0; RECORD_SIZE];
+        self.flash.read(self.v0_offset, &mut bytes).ok()?;
+        let preset = codec::decode_v0(&bytes).ok()?;
+        let end = self.v0_offset + F::ERASE_SIZE as u32;
+        self.flash.erase(self.v0_offset, end).ok();
+
+        Option::Some(preset)
+    }
+
+    fn read_preset(&mut self, slot: usize) -> Option<Preset> {
+        let mut bytes = [0; RECORD_SIZE];
+        if !self.preset_journals[slot].mount(&mut self.flash, &mut bytes) {
+            return Option::None
+        }
+        let preset = codec::decode(&bytes).ok()?;
+        self.presets[slot] = Option::Some(bytes);
+
+        Option::Some(preset)
+    }
+
+    fn write_preset(&mut self, slot: usize, preset: &Preset) -> Result<(), F::Error> {
+        let bytes = codec::encode(preset);
+        if self.presets[slot] == Option::Some(bytes) {
+            return Ok(())
+        }
+
+        self.preset_journals[slot].append(&mut self.flash, &bytes)?;
+        self.presets[slot] = Option::Some(bytes);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::{Bpm, Swing},
+        storage::fake_flash::{chip, FakeFlash, CAPACITY, ERASE_SIZE},
+    };
+
+    /// `state` with a preset at `bpm` in the first slot.
+    fn with_preset(state: &State, bpm: u32) -> State {
+        let mut state = state.clone();
+        let mut preset = state.preset();
+        preset.bpm = Bpm::from_whole(bpm);
+        state.presets[0] = Option::Some(preset);
+        state
+    }
+
+    /// What the module comes up with at power-up.
+    fn loaded(chip: &mut [u8; CAPACITY]) -> State {
+        let mut state = State::default();
+        Store::new(FakeFlash::new(chip), 0).load(&mut state);
+        state
+    }
+
+    fn preset_bpm(state: &State) -> Option<u32> {
+        state.presets[0].as_ref().map(|preset| preset.bpm.whole())
+    }
+
+    /// Settings at `bpm` where version 0 kept them, in the last sector.
+    fn write_v0(chip: &mut [u8; CAPACITY], bpm: u16) {
+        let sector = &mut chip[CAPACITY - ERASE_SIZE..];
+        sector[..4].copy_from_slice(b"TOAD");
+        sector[4..6].copy_from_slice(&bpm.to_le_bytes());
+        sector[6] = 0;
+        for output in sector[7..].chunks_exact_mut(8).take(4) {
+            // A gate at unity, pwm 50%, prob 100%, 16 steps of 4.
+            output.copy_from_slice(&[0, 0, 1, 0, 5, 9, 16, 4]);
+        }
+    }
+
+    fn last_sector_erased(chip: &[u8; CAPACITY]) -> bool {
+        chip[CAPACITY - ERASE_SIZE..]
+            .iter()
+            .all(|byte| *byte == 0xFF)
+    }
+
+    #[test]
+    fn loads_nothing_from_an_erased_chip() {
+        let state = loaded(&mut chip());
+
+        assert!(state.bpm == State::default().bpm);
+        assert!(state.presets.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn loads_what_it_saved() {
+        let mut chip = chip();
+        let mut state = with_preset(&State::default(), 90);
+        state.bpm = Bpm(1_335);
+        state.swing = Swing(62);
+        state.presets[5] = state.presets[0].clone();
+        Store::new(FakeFlash::new(&mut chip), 0)
+            .save(&state)
+            .unwrap();
+
+        let state = loaded(&mut chip);
+        assert!(state.bpm == Bpm(1_335));
+        assert!(state.swing == Swing(62));
+        assert_eq!(preset_bpm(&state), Option::Some(90));
+        assert!(state.presets[5].is_some());
+        assert!(state.presets[1].is_none());
+    }
+
+    #[test]
+    fn migrates_version_0_once() {
+        let mut chip = chip();
+        write_v0(&mut chip, 140);
+
+        let state = loaded(&mut chip);
+        assert!(state.bpm == Bpm::from_whole(140));
+        assert!(last_sector_erased(&chip));
+
+        Store::new(FakeFlash::new(&mut chip), 0)
+            .save(&state)
+            .unwrap();
+        assert!(loaded(&mut chip).bpm == Bpm::from_whole(140));
+    }
+
+    #[test]
+    fn keeps_journaled_settings_over_version_0() {
+        let mut chip = chip();
+        let state = State {
+            bpm: Bpm(1_335),
+            ..State::default()
+        };
+        Store::new(FakeFlash::new(&mut chip), 0)
+            .save(&state)
+            .unwrap();
+        write_v0(&mut chip, 140);
+
+        assert!(loaded(&mut chip).bpm == Bpm(1_335));
+        assert!(last_sector_erased(&chip));
+    }
+
+    #[test]
+    fn saves_nothing_unchanged() {
+        let mut chip = chip();
+        let state = with_preset(&State::default(), 90);
+        let mut store = Store::new(FakeFlash::new(&mut chip), 0);
+        store.save(&state).unwrap();
+
+        let mut store = Store::new(FakeFlash::cut_after(&mut chip, 0), 0);
+        store.load(&mut State::default());
+        assert!(store.save(&state).is_ok());
+    }
+
+    #[test]
+    fn skips_a_record_that_doesnt_validate() {
+        let mut chip = chip();
+        let mut state = with_preset(&State::default(), 90);
+        state.bpm = Bpm(1_335);
+        Store::new(FakeFlash::new(&mut chip), 0)
+            .save(&state)
+            .unwrap();
+        // A bit of the current settings' bpm lost, after the entry's sequence.
+        chip[4 + 8] ^= 1;
+
+        let state = loaded(&mut chip);
+        assert!(state.bpm == State::default().bpm);
+        assert_eq!(preset_bpm(&state), Option::Some(90));
+    }
+
+    /// Power goes at every byte of saving a preset, after it's been saved
+    /// often enough to have gone round its sectors.
+    #[test]
+    fn power_loss_keeps_the_old_preset_or_the_new_one() {
+        for saved in 1..6 {
+            for power in 0..2 * ERASE_SIZE {
+                let mut chip = chip();
+                let mut store = Store::new(FakeFlash::new(&mut chip), 0);
+                for bpm in 0..saved {
+                    store
+                        .save(&with_preset(&State::default(), 100 + bpm))
+                        .unwrap();
+                }
+
+                let mut state = loaded(&mut chip);
+                let mut store = Store::new(FakeFlash::cut_after(&mut chip, power), 0);
+                store.load(&mut state);
+                let expected = match store.save(&with_preset(&state, 200)) {
+                    Ok(()) => 200,
+                    Err(_) => 100 + saved - 1,
+                };
+
+                assert_eq!(
+                    preset_bpm(&loaded(&mut chip)),
+                    Option::Some(expected),
+                    "{saved} saved, power lost after {power} bytes"
+                );
+            }
+        }
+    }
+}
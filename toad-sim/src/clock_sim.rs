@@ -0,0 +1,121 @@
+//! Feeds `ClockIn` a jittery clock and checks that the ticks stay locked to
+//! it: after every edge exactly the right number of ticks have run, however
+//! the edges wander.
+
+use seq::Seq;
+use toad_core::{
+    clock::{ClockIn, OnClockLoss},
+    state::{Ppqn, State},
+};
+
+use crate::rng::Rng;
+
+const START: u64 = 1_000_000;
+const FREEWHEEL: u64 = 4_000_000;
+// Pulses to let the tracker settle before measuring.
+const WARM_UP: u64 = 8;
+
+/// Checks every edge finds the ticks where they should be, or says how often
+/// one didn't.
+pub fn run(bpm: u32, ppqn: Ppqn, jitter: u64, pulses: u64, seed: u64) -> Result<(), String> {
+    let resolution = Seq::new(bpm, State::initial().outputs).resolution();
+    let ticks_per_pulse = (resolution / ppqn.pulses()).max(1) as u64;
+    let period = 60_000_000 / (bpm as u64 * ppqn.pulses() as u64);
+
+    let mut rng = Rng::new(seed);
+    let mut clock = ClockIn::new(resolution, ppqn, FREEWHEEL, OnClockLoss::Stop);
+    let mut pulse = 0;
+    let mut ticks = 0;
+    let mut tick_errors = Errors::default();
+    let mut drifted = 0;
+
+    println!("{bpm} bpm at {ppqn} ppqn, edges ±{jitter}us, seed {seed}");
+
+    while pulse < pulses {
+        let offset = rng.next() % (2 * jitter + 1);
+        let next_edge = START + pulse * period + offset - jitter;
+
+        // Run the ticks between edges, then the edge itself.
+        while let Option::Some(deadline) = clock.deadline().filter(|at| *at < next_edge) {
+            let due = clock.poll(deadline);
+            for _ in 0..due {
+                let ideal = START + ticks * period / ticks_per_pulse;
+                if pulse > WARM_UP {
+                    tick_errors.record(deadline as i64 - ideal as i64);
+                }
+                ticks += 1;
+            }
+        }
+        ticks += clock.edge(next_edge) as u64;
+
+        if ticks != pulse * ticks_per_pulse + 1 {
+            drifted += 1;
+        }
+        pulse += 1;
+    }
+
+    let stats = clock.stats();
+    println!(
+        "edges off prediction: average {}us, max {}us over {} edges",
+        stats.average_error, stats.max_error, stats.edges
+    );
+    println!(
+        "ticks off ideal: average {}us, max {}us",
+        tick_errors.average(),
+        tick_errors.max
+    );
+
+    if drifted > 0 {
+        return Err(format!(
+            "ticks out of step with the clock on {drifted} of {pulses} edges"
+        ))
+    }
+
+    println!("locked on all {pulses} edges");
+    Ok(())
+}
+
+#[derive(Default)]
+struct Errors {
+    count: u64,
+    sum: u64,
+    max: u64,
+}
+
+impl Errors {
+    fn record(&mut self, error: i64) {
+        self.count += 1;
+        self.sum += error.unsigned_abs();
+        self.max = self.max.max(error.unsigned_abs());
+    }
+
+    fn average(&self) -> u64 {
+        self.sum / self.count.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PPQNS: [Ppqn; 6] = [Ppqn::P1, Ppqn::P2, Ppqn::P4, Ppqn::P8, Ppqn::P24, Ppqn::P48];
+
+    /// Edges up to a tenth of a pulse off, at a slow, a middling and the
+    /// fastest tempo and every ppqn.
+    #[test]
+    fn ticks_stay_locked_to_a_jittery_clock() {
+        for (seed, (bpm, ppqn)) in [40, 120, 300]
+            .into_iter()
+            .flat_map(|bpm| PPQNS.map(|ppqn| (bpm, ppqn)))
+            .enumerate()
+        {
+            let jitter = 60_000_000 / (bpm as u64 * ppqn.pulses() as u64) / 10;
+
+            assert_eq!(
+                run(bpm, ppqn, jitter, 200, seed as u64 + 1),
+                Ok(()),
+                "{bpm} bpm at {ppqn} ppqn"
+            );
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Drives random command sequences through the module and checks that the
+//! state stays sane after every command, and that nothing panics.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use embassy_time::Instant;
+use seq::OutputType;
+use toad_core::state::{
+    Button, Command, Element, Screen, State, StepRate, MAX_BPM, MAX_RATIO, MAX_SWING, MIN_BPM,
+    MIN_SWING, NAME_LENGTH,
+};
+
+use crate::{rng::Rng, stamped, Module};
+
+const COMMANDS: [Command; 11] = [
+    Command::EncoderRight(Instant::from_micros(0)),
+    Command::EncoderLeft(Instant::from_micros(0)),
+    Command::EncoderPress,
+    Command::PagePress,
+    Command::PlayPress,
+    Command::BpmPress(Instant::from_micros(0)),
+    Command::LongPress(Button::Play),
+    Command::LongPress(Button::Encoder),
+    Command::DoublePress(Button::Encoder),
+    Command::Chord(Button::Page, Button::Play),
+    Command::Chord(Button::Encoder, Button::Bpm),
+];
+const RUN_LENGTH: usize = 500;
+// Commands are up to this far apart, fast enough to spin the encoder.
+const MAX_GAP_MICROS: u64 = 200_000;
+
+/// Runs `runs` sequences and returns the first that didn't hold up. Pass the
+/// seed printed at the start to replay it.
+pub fn run(runs: usize, seed: Option<u64>) -> Result<(), String> {
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |duration| duration.as_nanos() as u64)
+    });
+    println!("seed {seed}");
+
+    let mut rng = Rng::new(seed);
+    for run in 0..runs {
+        let mut at = 0;
+        let commands: Vec<Command> = (0..RUN_LENGTH)
+            .map(|_| {
+                at += rng.next() % MAX_GAP_MICROS;
+                stamped(COMMANDS[rng.below(COMMANDS.len())], at)
+            })
+            .collect();
+
+        if let Err((step, message)) = play(&commands) {
+            let names: Vec<String> = commands[..=step].iter().map(name).collect();
+            return Err(format!(
+                "run {run} failed after {} commands: {message}\n{}",
+                step + 1,
+                names.join(" ")
+            ))
+        }
+    }
+
+    println!("{runs} runs of {RUN_LENGTH} commands ok");
+    Ok(())
+}
+
+fn play(commands: &[Command]) -> Result<(), (usize, String)> {
+    let mut module = Module::new(State::initial());
+    for (step, command) in commands.iter().enumerate() {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            module.command(*command);
+            module.tick();
+        }))
+        .map_err(|_| (step, "panicked".into()))?;
+        check(&module.state).map_err(|message| (step, message))?;
+    }
+
+    Ok(())
+}
+
+fn check(state: &State) -> Result<(), String> {
+    if !(MIN_BPM * 10..=MAX_BPM * 10).contains(&state.bpm.0) {
+        return Err(format!("bpm {} out of range", state.bpm))
+    }
+    let mut swings = state.swings.iter().flatten().chain([&state.swing]);
+    if let Some(swing) = swings.find(|swing| !(MIN_SWING..=MAX_SWING).contains(&swing.0)) {
+        return Err(format!("swing {swing} out of range"))
+    }
+
+    for (idx, (config, rotation)) in state.outputs.iter().zip(state.rotations).enumerate() {
+        if rotation.0 as u32 >= config.length().0 as u32 {
+            return Err(format!(
+                "output {idx} rotation {} past length {}",
+                rotation.0,
+                config.length().0
+            ))
+        }
+        if config.rate() != state.rates[idx].seq_rate() {
+            return Err(format!("output {idx} rate doesn't match what seq plays"))
+        }
+        if let StepRate::Ratio(steps, beats) = state.rates[idx] {
+            if !(1..=MAX_RATIO).contains(&steps) || !(1..=MAX_RATIO).contains(&beats) {
+                return Err(format!("output {idx} ratio {steps}/{beats} out of range"))
+            }
+        }
+        if config.density().0 > config.length().0 {
+            return Err(format!(
+                "output {idx} density {} over length {}",
+                config.density().0,
+                config.length().0
+            ))
+        }
+    }
+
+    if state.name_char >= NAME_LENGTH {
+        return Err(format!("name character {} past the name", state.name_char))
+    }
+
+    let valid = match state.current_screen {
+        Screen::Home => matches!(
+            state.current_element,
+            Element::Bpm | Element::Sync | Element::Ppqn | Element::Swing
+        ),
+        Screen::Output(output, output_type) => {
+            let config = &state.outputs[usize::from(output)];
+            if config.output_type() != output_type {
+                return Err("screen doesn't match the output's type".into())
+            }
+            let ratio = matches!(state.rates[usize::from(output)], StepRate::Ratio(..));
+            if matches!(state.current_element, Element::Beats) && !ratio {
+                return Err("beats selected without a ratio rate".into())
+            }
+            match output_type {
+                OutputType::Gate => matches!(
+                    state.current_element,
+                    Element::Rate
+                        | Element::Beats
+                        | Element::Prob
+                        | Element::Pwm
+                        | Element::Swing
+                        | Element::OutputType
+                ),
+                OutputType::Euclid => matches!(
+                    state.current_element,
+                    Element::Rate
+                        | Element::Beats
+                        | Element::Length
+                        | Element::Density
+                        | Element::Rotation
+                        | Element::Swing
+                        | Element::OutputType
+                ),
+            }
+        }
+        Screen::Presets => matches!(
+            state.current_element,
+            Element::Slot | Element::Name | Element::Load | Element::Save
+        ),
+    };
+    if !valid {
+        return Err("current element isn't on the current screen".into())
+    }
+
+    Ok(())
+}
+
+fn name(command: &Command) -> String {
+    match command {
+        Command::EncoderRight(_) => "right".into(),
+        Command::EncoderLeft(_) => "left".into(),
+        Command::EncoderPress => "press".into(),
+        Command::PagePress => "page".into(),
+        Command::PlayPress => "play".into(),
+        Command::BpmPress(_) => "bpm".into(),
+        Command::LongPress(button) => format!("long-{}", button_name(button)),
+        Command::DoublePress(button) => format!("double-{}", button_name(button)),
+        Command::Chord(first, second) => {
+            format!("{}+{}", button_name(first), button_name(second))
+        }
+    }
+}
+
+fn button_name(button: &Button) -> &'static str {
+    match button {
+        Button::Bpm => "bpm",
+        Button::Play => "play",
+        Button::Page => "page",
+        Button::Encoder => "encoder",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_holds_up_to_random_commands() {
+        assert_eq!(run(20, Option::Some(1)), Ok(()));
+    }
+}
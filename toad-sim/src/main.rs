@@ -0,0 +1,295 @@
+use std::{
+    env, io, process,
+    time::{Duration, Instant},
+};
+
+use seq::OutputType;
+use toad_core::{
+    animator::Animator,
+    display::Display,
+    gestures::{Gestures, Timings},
+    screens::Screens,
+    sequencer::Sequencer,
+    state::{Button, Command, Output, PlayStatus, Ppqn, Screen, ScreenState, State, StateChange},
+    state_memo::StateMemo,
+};
+
+use crate::{
+    framebuffer::Framebuffer,
+    terminal::{Key, Terminal},
+};
+
+mod clock_sim;
+mod framebuffer;
+mod fuzz;
+mod rng;
+mod snapshots;
+mod swing_sim;
+mod taps_sim;
+mod terminal;
+
+const FRAME_DURATION: Duration = Duration::from_millis(33);
+// Don't try to catch up on more than this many ticks if the loop stalls.
+const MAX_TICKS_BEHIND: u32 = 10_000;
+
+/// Everything the two cores do on the module, minus the hardware.
+struct Module {
+    state: State,
+    seq: Sequencer,
+    memo: StateMemo,
+    screens: Screens,
+    display: Display<Framebuffer>,
+    animator: Animator,
+    gestures: Gestures,
+    gates: [bool; 4],
+}
+
+impl Module {
+    fn new(state: State) -> Self {
+        let seq = Sequencer::new(&state.preset());
+        let animator = Animator::new(seq.resolution(), 1);
+        let memo = StateMemo::new(&state);
+        let mut module = Self {
+            state,
+            seq,
+            memo,
+            screens: Screens::new(),
+            display: Display::new(Framebuffer::new()),
+            animator,
+            gestures: Gestures::new(Timings::default()),
+            gates: [false; 4],
+        };
+
+        let next_screen = StateChange::NextScreen(ScreenState::new_home(
+            module.state.bpm,
+            module.state.sync,
+            module.state.ppqn,
+            module.state.swing,
+            module.state.play_status,
+        ));
+        module.screens.draw(next_screen, &mut module.display);
+
+        module
+    }
+
+    fn tick_duration(&self) -> Duration {
+        Duration::from_micros(self.memo.bpm.tick_duration_micros(self.seq.resolution()))
+    }
+
+    fn command(&mut self, command: Command) {
+        if let Some(state_change) = self.state.handle_command(command) {
+            self.memo.update(&state_change);
+            state_change.update_seq(&mut self.seq);
+            let state_change = state_change.update_index(&self.seq);
+            let restarted = matches!(
+                state_change,
+                StateChange::Stop(..) | StateChange::Downbeat(..)
+            );
+            self.screens.draw(state_change, &mut self.display);
+
+            if restarted {
+                self.gates = [false; 4];
+                if let Screen::Output(output, OutputType::Euclid) = self.memo.current_screen {
+                    let index = self.seq.get_index(usize::from(output));
+                    self.screens
+                        .draw(StateChange::Index(output, index), &mut self.display);
+                }
+            }
+        }
+    }
+
+    /// Buttons go through `Gestures` like the module's do, with times in
+    /// microseconds.
+    fn down(&mut self, button: Button, at: u64) {
+        if let Some(command) = self.gestures.down(button, at) {
+            self.command(command);
+        }
+    }
+
+    fn up(&mut self, button: Button, at: u64) {
+        if let Some(command) = self.gestures.up(button, at) {
+            self.command(command);
+        }
+    }
+
+    /// Sends whatever `Gestures` has come due by `now`.
+    fn poll(&mut self, now: u64) {
+        while let Some(command) = self.gestures.poll(now) {
+            self.command(command);
+        }
+    }
+
+    /// What the jacks are putting out, which is nothing unless playing.
+    fn outputs(&self) -> [bool; 4] {
+        match self.memo.play_status {
+            PlayStatus::Playing => self.gates,
+            PlayStatus::Paused | PlayStatus::Stopped => [false; 4],
+        }
+    }
+
+    fn tick(&mut self) {
+        if !matches!(self.memo.play_status, PlayStatus::Playing) {
+            return
+        }
+
+        let toggles = self.seq.tick();
+        for idx in 0..self.gates.len() {
+            if toggles[idx] {
+                self.gates[idx] = !self.gates[idx];
+            }
+            if self.seq.get_index_change(idx) {
+                let current_output = Output::into_output(idx);
+                if self.memo.current_screen.is_euclid(current_output) {
+                    let state_change = StateChange::Index(current_output, self.seq.get_index(idx));
+                    self.screens.draw(state_change, &mut self.display);
+                }
+            }
+        }
+
+        self.animator.update();
+        if self.animator.next_frame() {
+            self.screens.draw(StateChange::Frame, &mut self.display);
+        }
+    }
+}
+
+/// `command` as if it happened `at`, for the commands that carry a time.
+fn stamped(command: Command, at: u64) -> Command {
+    let at = embassy_time::Instant::from_micros(at);
+    match command {
+        Command::EncoderRight(_) => Command::EncoderRight(at),
+        Command::EncoderLeft(_) => Command::EncoderLeft(at),
+        Command::BpmPress(_) => Command::BpmPress(at),
+        command => command,
+    }
+}
+
+/// The number in `args` at `idx`, or `default` if there isn't one.
+fn arg(args: &[String], idx: usize, default: u64) -> u64 {
+    args.get(idx)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Exits with the failure a check ran into, once it's printed.
+fn finish(result: Result<(), String>) -> io::Result<()> {
+    if let Err(message) = result {
+        println!("{message}");
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let args: std::vec::Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("snapshots") => {
+            let bless = args.iter().any(|arg| arg == "--bless");
+            if !snapshots::run(bless)? {
+                process::exit(1);
+            }
+            return Ok(())
+        }
+        Some("fuzz") => {
+            let runs = args
+                .get(1)
+                .and_then(|runs| runs.parse().ok())
+                .unwrap_or(1_000);
+            let seed = args.get(2).and_then(|seed| seed.parse().ok());
+            return finish(fuzz::run(runs, seed))
+        }
+        Some("clock") => {
+            let ppqn = match arg(&args, 2, 24) {
+                1 => Ppqn::P1,
+                2 => Ppqn::P2,
+                4 => Ppqn::P4,
+                8 => Ppqn::P8,
+                48 => Ppqn::P48,
+                _ => Ppqn::P24,
+            };
+            return finish(clock_sim::run(
+                arg(&args, 1, 120) as u32,
+                ppqn,
+                arg(&args, 3, 500),
+                arg(&args, 4, 1_000),
+                arg(&args, 5, 1),
+            ))
+        }
+        Some("taps") if args.get(1).map(String::as_str) == Some("replay") => {
+            let (Some(path), Some(bpm)) =
+                (args.get(2), args.get(3).and_then(|bpm| bpm.parse().ok()))
+            else {
+                eprintln!("usage: taps replay <file> <bpm>");
+                process::exit(2);
+            };
+            return finish(taps_sim::replay(path, bpm)?)
+        }
+        Some("swing") => {
+            return finish(swing_sim::run(
+                arg(&args, 1, 67) as u8,
+                arg(&args, 2, 96),
+                arg(&args, 3, 64),
+            ))
+        }
+        Some("taps") => {
+            return finish(taps_sim::run(
+                arg(&args, 1, 120) as u32,
+                arg(&args, 2, 20_000),
+                arg(&args, 3, 8),
+                arg(&args, 4, 1),
+            ))
+        }
+        _ => {}
+    }
+
+    let mut module = Module::new(State::initial());
+    let mut terminal = Terminal::new()?;
+
+    // The same clock as the encoder's turns, so everything lines up.
+    let micros = || embassy_time::Instant::now().as_micros();
+    let mut next_tick = Instant::now();
+    let mut next_frame = Instant::now();
+
+    loop {
+        let timeout = next_tick
+            .min(next_frame)
+            .saturating_duration_since(Instant::now());
+        // A terminal only sees keys go down, so a key is a press and let go
+        // straight away, or held as long as a long press takes.
+        match terminal.poll_key(timeout)? {
+            Some(Key::Quit) => break,
+            Some(Key::Command(command)) => module.command(command),
+            Some(Key::Press(button)) => {
+                let at = micros();
+                module.down(button, at);
+                module.up(button, at);
+            }
+            Some(Key::Hold(button)) => {
+                let at = micros();
+                module.down(button, at);
+                module.up(button, at + Timings::default().long_press);
+            }
+            None => {}
+        }
+        module.poll(micros());
+
+        let now = Instant::now();
+        let mut ticks = 0;
+        while next_tick <= now && ticks < MAX_TICKS_BEHIND {
+            module.tick();
+            next_tick += module.tick_duration();
+            ticks += 1;
+        }
+        if ticks == MAX_TICKS_BEHIND {
+            next_tick = now;
+        }
+
+        if next_frame <= now {
+            terminal.render(module.display.inner(), &module.outputs())?;
+            next_frame = now + FRAME_DURATION;
+        }
+    }
+
+    Ok(())
+}
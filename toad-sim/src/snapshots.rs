@@ -0,0 +1,329 @@
+//! Golden-image checks for the screens.
+//!
+//! Each case plays a script of commands and ticks through the same pipeline
+//! as the module, then compares the framebuffer to `snapshots/<name>.pbm`.
+//! References are plain (P1) PBMs, one pixel row per line, so they diff
+//! sensibly in git. Pass `--bless` to rewrite them after an intended change.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use embassy_time::Instant;
+use toad_core::{
+    gestures::Timings,
+    state::{Button, Command, State},
+};
+
+use crate::{
+    framebuffer::{Framebuffer, HEIGHT, WIDTH},
+    stamped, Module,
+};
+
+type Pixels = [[bool; WIDTH]; HEIGHT];
+
+enum Step {
+    Command(Command),
+    /// Presses a button this many times in quick succession, through
+    /// `Gestures` like the module's buttons.
+    Presses(Button, u32),
+    /// Holds a button for a long press.
+    Hold(Button),
+    /// Plays until output A has moved on this many steps.
+    Steps(u32),
+}
+
+struct Case {
+    name: &'static str,
+    script: &'static [Step],
+}
+
+const RIGHT: Step = Step::Command(Command::EncoderRight(Instant::from_micros(0)));
+const LEFT: Step = Step::Command(Command::EncoderLeft(Instant::from_micros(0)));
+const PRESS: Step = Step::Presses(Button::Encoder, 1);
+const PAGE: Step = Step::Presses(Button::Page, 1);
+const PLAY: Step = Step::Presses(Button::Play, 1);
+const DOUBLE: Step = Step::Presses(Button::Encoder, 2);
+const HOLD: Step = Step::Hold(Button::Encoder);
+// How long a button is held, and how long it's up between quick presses.
+const PRESS_MICROS: u64 = 50_000;
+
+const CASES: &[Case] = &[
+    Case {
+        name: "home",
+        script: &[],
+    },
+    Case {
+        name: "home_bpm",
+        script: &[RIGHT, RIGHT, RIGHT],
+    },
+    Case {
+        name: "home_sync",
+        script: &[PRESS, RIGHT],
+    },
+    Case {
+        name: "home_ppqn",
+        script: &[PRESS, PRESS, RIGHT],
+    },
+    Case {
+        name: "home_paused",
+        script: &[PLAY],
+    },
+    Case {
+        name: "euclid",
+        script: &[PAGE],
+    },
+    Case {
+        name: "euclid_length",
+        script: &[PAGE, PRESS, LEFT, LEFT, LEFT, LEFT],
+    },
+    Case {
+        name: "euclid_density",
+        script: &[PAGE, PRESS, PRESS, LEFT, LEFT, LEFT],
+    },
+    Case {
+        name: "euclid_rotation",
+        script: &[PAGE, PRESS, PRESS, PRESS, RIGHT, RIGHT, RIGHT],
+    },
+    Case {
+        name: "euclid_long",
+        script: &[
+            PAGE, PRESS, RIGHT, RIGHT, RIGHT, RIGHT, RIGHT, RIGHT, RIGHT, RIGHT,
+        ],
+    },
+    Case {
+        name: "euclid_index",
+        script: &[PAGE, Step::Steps(5)],
+    },
+    Case {
+        name: "euclid_output_type",
+        script: &[PAGE, PRESS, PRESS, PRESS, PRESS, PRESS],
+    },
+    Case {
+        name: "gate",
+        script: &[PAGE, PAGE],
+    },
+    Case {
+        name: "gate_rate",
+        script: &[PAGE, PAGE, RIGHT, RIGHT],
+    },
+    Case {
+        name: "gate_ratio",
+        script: &[PAGE, PAGE, DOUBLE, RIGHT, RIGHT],
+    },
+    Case {
+        name: "gate_ratio_beats",
+        script: &[PAGE, PAGE, DOUBLE, PRESS, RIGHT, RIGHT, RIGHT],
+    },
+    Case {
+        name: "gate_coarse",
+        script: &[PAGE, PAGE, HOLD, HOLD],
+    },
+    Case {
+        name: "gate_prob",
+        script: &[PAGE, PAGE, PRESS, LEFT, LEFT],
+    },
+    Case {
+        name: "gate_pwm",
+        script: &[PAGE, PAGE, PRESS, PRESS, RIGHT],
+    },
+    Case {
+        name: "gate_swing",
+        script: &[PAGE, PAGE, PRESS, PRESS, PRESS, RIGHT, RIGHT],
+    },
+    Case {
+        name: "presets",
+        script: &[PAGE, PAGE, PAGE, PAGE, PAGE],
+    },
+    Case {
+        name: "presets_name",
+        script: &[PAGE, PAGE, PAGE, PAGE, PAGE, PRESS, PRESS, RIGHT, RIGHT],
+    },
+    Case {
+        name: "presets_saved",
+        script: &[
+            PAGE, PAGE, PAGE, PAGE, PAGE, RIGHT, PRESS, PRESS, PRESS, PRESS, PRESS, PRESS, PRESS,
+            RIGHT, PRESS,
+        ],
+    },
+];
+
+/// Checks every case, or rewrites the references when `bless` is set.
+/// Returns whether everything matched.
+pub fn run(bless: bool) -> io::Result<bool> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+    let mut passed = true;
+
+    for case in CASES {
+        let actual = render(case);
+        let path = path(&dir, case.name);
+
+        if bless {
+            fs::create_dir_all(&dir)?;
+            fs::write(&path, to_pbm(&actual))?;
+            println!("blessed {}", case.name);
+            continue;
+        }
+
+        let expected = match fs::read_to_string(&path) {
+            Ok(pbm) => from_pbm(&pbm).ok_or_else(|| {
+                io::Error::new(ErrorKind::InvalidData, path.display().to_string())
+            })?,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                println!("missing {} (bless to write it)", case.name);
+                passed = false;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+
+        if expected == actual {
+            println!("ok      {}", case.name);
+        } else {
+            println!("FAILED  {}", case.name);
+            print!("{}", diff(&expected, &actual));
+            passed = false;
+        }
+    }
+
+    Ok(passed)
+}
+
+fn render(case: &Case) -> Pixels {
+    let mut module = Module::new(State::initial());
+    for (idx, step) in case.script.iter().enumerate() {
+        // A second apart, slow enough that the encoder doesn't speed up and
+        // a held back press has gone out.
+        let at = idx as u64 * 1_000_000;
+        module.poll(at);
+        match step {
+            Step::Command(command) => module.command(stamped(*command, at)),
+            Step::Presses(button, presses) => {
+                for press in 0..*presses as u64 {
+                    let down = at + press * 2 * PRESS_MICROS;
+                    module.down(*button, down);
+                    module.up(*button, down + PRESS_MICROS);
+                }
+            }
+            Step::Hold(button) => {
+                module.down(*button, at);
+                module.poll(at + Timings::default().long_press);
+                module.up(*button, at + Timings::default().long_press);
+            }
+            Step::Steps(steps) => {
+                for _ in 0..*steps {
+                    module.tick();
+                    while !module.seq.get_index_change(0) {
+                        module.tick();
+                    }
+                }
+            }
+        }
+    }
+    module.poll(case.script.len() as u64 * 1_000_000);
+
+    pixels(module.display.inner())
+}
+
+fn path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(name).with_extension("pbm")
+}
+
+fn pixels(framebuffer: &Framebuffer) -> Pixels {
+    let mut pixels = [[false; WIDTH]; HEIGHT];
+    for (y, row) in pixels.iter_mut().enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = framebuffer.pixel(x, y);
+        }
+    }
+
+    pixels
+}
+
+fn to_pbm(pixels: &Pixels) -> String {
+    let mut pbm = format!("P1\n{WIDTH} {HEIGHT}\n");
+    for row in pixels {
+        pbm.extend(row.iter().map(|&on| if on { '1' } else { '0' }));
+        pbm.push('\n');
+    }
+
+    pbm
+}
+
+fn from_pbm(pbm: &str) -> Option<Pixels> {
+    let mut tokens = pbm
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(str::split_whitespace);
+
+    if tokens.next()? != "P1"
+        || tokens.next()?.parse::<usize>().ok()? != WIDTH
+        || tokens.next()?.parse::<usize>().ok()? != HEIGHT
+    {
+        return Option::None
+    }
+
+    let mut bits = tokens.flat_map(str::chars);
+    let mut pixels = [[false; WIDTH]; HEIGHT];
+    for row in pixels.iter_mut() {
+        for pixel in row.iter_mut() {
+            *pixel = match bits.next()? {
+                '0' => false,
+                '1' => true,
+                _ => return Option::None,
+            };
+        }
+    }
+
+    Option::Some(pixels)
+}
+
+/// Draws both images at once: `#` lit in both, `-` only in the reference,
+/// `+` only in the new render. Unchanged rows are elided.
+fn diff(expected: &Pixels, actual: &Pixels) -> String {
+    let mut out = String::new();
+    let mut elided = false;
+
+    for (y, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+        if expected == actual {
+            elided = true;
+            continue;
+        }
+        if elided {
+            out.push_str("   ...\n");
+            elided = false;
+        }
+
+        write!(out, "{y:>2} ").ok();
+        out.extend(expected.iter().zip(actual).map(|pixels| match pixels {
+            (true, true) => '#',
+            (true, false) => '-',
+            (false, true) => '+',
+            (false, false) => '.',
+        }));
+        out.push('\n');
+    }
+    if elided {
+        out.push_str("   ...\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// Rewrites the references instead with `TOAD_BLESS` set.
+    #[test]
+    fn screens_match_their_references() {
+        let bless = env::var_os("TOAD_BLESS").is_some();
+
+        assert!(run(bless).unwrap(), "screens don't match their references");
+    }
+}
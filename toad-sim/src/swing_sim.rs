@@ -0,0 +1,105 @@
+//! Runs steady gates through `Groove` and checks they come out swung: every
+//! even step on the grid, every odd one late by the swing's share of the
+//! pair, and no toggle lost, even from triggers a single tick long.
+
+use toad_core::{
+    groove::{self, Groove, Timing},
+    state::{Swing, MAX_SWING, MIN_SWING},
+};
+
+/// Checks every step landed where the swing puts it, or says which didn't.
+pub fn run(percent: u8, step: u64, steps: u64) -> Result<(), String> {
+    if !(MIN_SWING..=MAX_SWING).contains(&percent) || step < 2 {
+        return Err(format!(
+            "swing runs {MIN_SWING}..={MAX_SWING}%, with steps two ticks or longer"
+        ))
+    }
+
+    let swing = Swing(percent);
+    let late = groove::delay(step, swing, step);
+    let mut groove = Groove::new();
+    // A half-step gate on the first output, a one-tick trigger on the second.
+    let mut levels = [false; 2];
+    let mut rises: [std::vec::Vec<u64>; 2] = Default::default();
+    let mut toggles = [0; 2];
+
+    println!("{swing} swing, {step} ticks a step, {steps} steps, odd steps {late} ticks late");
+
+    for tick in 0..(steps + 1) * step {
+        let phase = tick % step;
+        let playing = tick < steps * step;
+        let toggled = [
+            (playing && (phase == 0 || phase == step / 2)) as u8,
+            (playing && (phase == 0 || phase == 1)) as u8,
+            0,
+            0,
+        ];
+        let timing = Timing {
+            swing,
+            step,
+            late: 0,
+        };
+        let swung = groove.tick(toggled, [timing; 4]);
+
+        for (idx, level) in levels.iter_mut().enumerate() {
+            toggles[idx] += toggled[idx] as u64;
+            if swung[idx] {
+                *level = !*level;
+                toggles[idx] -= 1;
+                if *level {
+                    rises[idx].push(tick);
+                }
+            }
+        }
+    }
+
+    let mut failures = std::vec::Vec::new();
+    for (idx, rises) in rises.iter().enumerate() {
+        let off_grid = rises
+            .iter()
+            .enumerate()
+            .filter(|(k, at)| {
+                let k = *k as u64;
+                **at != k * step + if k % 2 == 1 { late } else { 0 }
+            })
+            .count();
+        let report = format!(
+            "output {}: {} of {steps} steps, {off_grid} off the swung grid, {} toggles lost",
+            idx + 1,
+            rises.len(),
+            toggles[idx]
+        );
+        println!("{report}");
+        if rises.len() as u64 != steps || off_grid > 0 || toggles[idx] > 0 || levels[idx] {
+            failures.push(report);
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "gates didn't swing right on {}",
+            failures.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every swing, down to the shortest steps.
+    #[test]
+    fn gates_swing_at_every_percent() {
+        for percent in MIN_SWING..=MAX_SWING {
+            for step in [2, 3, 24, 96] {
+                assert_eq!(
+                    run(percent, step, 16),
+                    Ok(()),
+                    "{percent}% with {step} tick steps"
+                );
+            }
+        }
+    }
+}
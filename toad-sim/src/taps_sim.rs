@@ -0,0 +1,246 @@
+//! Taps a tempo into the BPM button the way a person would, a little uneven
+//! and now and then missing a tap, hitting one twice or catching one between
+//! beats, with every press handled after a random delay. Checks the tempo
+//! still comes out right with every way of averaging and rounding, and with
+//! the internal clock, that the sequences start over from the last tap that
+//! counted. Presses are timed from when they happened, so the delay doesn't
+//! count; what timing them from handling would have come to is shown
+//! alongside.
+//!
+//! Recorded taps can be replayed too: a file of press times in microseconds,
+//! one per line, with `#` starting a comment.
+
+use std::{fs, io};
+
+use embassy_time::Instant;
+use toad_core::state::{Averaging, Command, Rounding, State, StateChange, Sync, TapSettings};
+
+use crate::rng::Rng;
+
+const START: u64 = 1_000_000;
+// One in this many taps is missed, one hit twice and one has an extra tap
+// in the middle third of the beat after it. Slips close together look like
+// a new tempo rather than slips, so this many taps after one are clean.
+const SLIP_EVERY: u64 = 12;
+const CLEAN_AFTER_SLIP: u64 = 2;
+// Taps land up to this share of a beat either side of it.
+const JITTER_SHARE: u64 = 50;
+// How far after a tap its accidental double lands.
+const DOUBLE_TAP: u64 = 40_000;
+
+/// Every way of averaging and rounding.
+const SETTINGS: [(Averaging, Rounding); 4] = [
+    (Averaging::Median, Rounding::Whole),
+    (Averaging::Median, Rounding::Tenths),
+    (Averaging::Weighted, Rounding::Whole),
+    (Averaging::Weighted, Rounding::Tenths),
+];
+
+/// Checks the tapped tempo comes out right, or says how it didn't.
+pub fn run(bpm: u32, max_delay: u64, taps: u64, seed: u64) -> Result<(), String> {
+    let presses = presses(bpm, taps, seed);
+    let handled = handled(&presses, max_delay, seed);
+
+    println!(
+        "{taps} taps at {bpm} bpm ±{}us, handled up to {max_delay}us late, seed {seed}",
+        60_000_000 / bpm as u64 / JITTER_SHARE
+    );
+
+    // Every one is checked, so a failure shows them all.
+    let failures: std::vec::Vec<String> = SETTINGS
+        .into_iter()
+        .map(|(averaging, rounding)| TapSettings {
+            averaging,
+            rounding,
+            ..TapSettings::DEFAULT
+        })
+        .filter_map(|settings| {
+            println!("{}:", describe(settings));
+            println!(
+                "  timed from the press: {} bpm",
+                tap(&presses, settings).bpm
+            );
+            println!("  timed from handling: {} bpm", tap(&handled, settings).bpm);
+            check(&presses, bpm, settings)
+                .map_err(|failure| format!("{}: {failure}", describe(settings)))
+                .err()
+        })
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Replays recorded taps and checks they came out at `bpm`.
+pub fn replay(path: &str, bpm: u32) -> io::Result<Result<(), String>> {
+    let presses = read(path)?;
+
+    println!("{} recorded taps from {path}", presses.len());
+
+    Ok(check(&presses, bpm, TapSettings::DEFAULT))
+}
+
+fn read(path: &str) -> io::Result<std::vec::Vec<u64>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| line.split('#').next()?.trim().parse().ok())
+        .collect())
+}
+
+/// When `taps` taps at `bpm` are pressed.
+fn presses(bpm: u32, taps: u64, seed: u64) -> std::vec::Vec<u64> {
+    let period = 60_000_000 / bpm as u64;
+    let jitter = period / JITTER_SHARE;
+
+    let mut rng = Rng::new(seed);
+    let mut presses = std::vec::Vec::new();
+    // The first tap can't be missed.
+    let mut clean = 1;
+    for tap in 0..taps {
+        let pressed = START + tap * period + rng.next() % (2 * jitter + 1) - jitter;
+        let slip = rng.next() % SLIP_EVERY;
+        match slip {
+            _ if clean > 0 => {
+                presses.push(pressed);
+                clean -= 1;
+                continue
+            }
+            0 => {}
+            1 => presses.extend([pressed, pressed + DOUBLE_TAP]),
+            2 if tap + 1 < taps => {
+                let between = period / 3 + rng.next() % (period / 3);
+                presses.extend([pressed, pressed + between]);
+            }
+            _ => {
+                presses.push(pressed);
+                continue
+            }
+        }
+        clean = CLEAN_AFTER_SLIP;
+    }
+
+    presses
+}
+
+/// When `presses` are handled, each up to `max_delay` late.
+fn handled(presses: &[u64], max_delay: u64, seed: u64) -> std::vec::Vec<u64> {
+    let mut rng = Rng::new(seed);
+    let mut handled = 0;
+
+    presses
+        .iter()
+        .map(|pressed| {
+            // Commands are handled in order, so a press can't overtake the last.
+            handled = (pressed + rng.next() % (max_delay + 1)).max(handled);
+            handled
+        })
+        .collect()
+}
+
+/// Taps the BPM button at each of `times` and returns the state it leaves.
+fn tap(times: &[u64], settings: TapSettings) -> State {
+    let mut state = State::initial();
+    state.tap_settings = settings;
+    for at in times {
+        state.handle_command(Command::BpmPress(Instant::from_micros(*at)));
+    }
+
+    state
+}
+
+fn check(presses: &[u64], bpm: u32, settings: TapSettings) -> Result<(), String> {
+    let mut state = State::initial();
+    state.tap_settings = settings;
+    let realign = matches!(state.sync, Sync::Int) && settings.realign;
+    let mut downbeat = Option::None;
+    let mut counted = Option::None;
+
+    for pressed in presses {
+        match state.handle_command(Command::BpmPress(Instant::from_micros(*pressed))) {
+            Option::Some(StateChange::Downbeat(_, at)) => {
+                downbeat = Option::Some(at);
+                counted = Option::Some(*pressed);
+            }
+            Option::Some(StateChange::Bpm(_)) => counted = Option::Some(*pressed),
+            _ => {}
+        }
+    }
+
+    // As close as the taps keep to the beat, or a whole BPM.
+    if state.bpm.0.abs_diff(bpm * 10) > (bpm * 10 / JITTER_SHARE as u32).max(10) {
+        return Err(format!("tapped tempo is off at {} bpm", state.bpm))
+    }
+    if realign && (downbeat.is_none() || downbeat != counted) {
+        return Err(format!(
+            "downbeat at {downbeat:?}us isn't on the last counted tap at {counted:?}us"
+        ))
+    }
+    if !realign && downbeat.is_some() {
+        return Err("downbeat moved without realigning".into())
+    }
+
+    Ok(())
+}
+
+fn describe(settings: TapSettings) -> String {
+    let averaging = match settings.averaging {
+        Averaging::Median => "median",
+        Averaging::Weighted => "weighted",
+    };
+    let rounding = match settings.rounding {
+        Rounding::Whole => "whole",
+        Rounding::Tenths => "tenths",
+    };
+
+    format!(
+        "window {}, {averaging} averaging, rounded to {rounding}",
+        settings.window
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// Uneven taps with slips, handled up to 20ms late, across the range.
+    #[test]
+    fn tapped_tempo_comes_out_right() {
+        for bpm in [40, 93, 120, 175, 300] {
+            for seed in 1..=5 {
+                assert_eq!(run(bpm, 20_000, 24, seed), Ok(()), "{bpm} bpm, seed {seed}");
+            }
+        }
+    }
+
+    /// Every file in `taps` is replayed at the tempo its name starts with.
+    #[test]
+    fn recorded_taps_come_out_at_their_tempo() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("taps");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            let bpm = name.split('_').next().unwrap().parse().unwrap();
+
+            assert_eq!(
+                replay(path.to_str().unwrap(), bpm).unwrap(),
+                Ok(()),
+                "{name}"
+            );
+        }
+    }
+    /// Handled up to 60ms late, taps at 175 bpm come to 182 bpm if they're
+    /// timed from handling rather than from the press.
+    #[test]
+    fn taps_are_timed_from_the_press() {
+        let presses = presses(175, 24, 1);
+        let handled = handled(&presses, 60_000, 1);
+
+        assert_eq!(check(&presses, 175, TapSettings::DEFAULT), Ok(()));
+        assert_eq!(tap(&handled, TapSettings::DEFAULT).bpm.0, 1_820);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    io::{self, Stdout, Write},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    execute, queue,
+    style::Print,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use embassy_time::Instant;
+use toad_core::state::{Button, Command};
+
+use crate::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+
+const HELP: &str =
+    "←/→ encoder  enter press  tab page  space play  s stop  f edit mode  b bpm  q quit";
+
+pub enum Key {
+    Command(Command),
+    /// A button pressed and let go.
+    Press(Button),
+    /// A button held for a long press.
+    Hold(Button),
+    Quit,
+}
+
+/// Raw-mode terminal that draws the framebuffer two pixel rows per line.
+pub struct Terminal {
+    stdout: Stdout,
+}
+
+impl Terminal {
+    pub fn new() -> io::Result<Self> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, Hide)?;
+
+        Ok(Self { stdout })
+    }
+
+    pub fn poll_key(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        if !event::poll(timeout)? {
+            return Ok(Option::None)
+        }
+
+        let key = match event::read()? {
+            Event::Key(KeyEvent {
+                code,
+                kind: KeyEventKind::Press,
+                ..
+            }) => match code {
+                KeyCode::Right | KeyCode::Char('l') => {
+                    Key::Command(Command::EncoderRight(Instant::now()))
+                }
+                KeyCode::Left | KeyCode::Char('h') => {
+                    Key::Command(Command::EncoderLeft(Instant::now()))
+                }
+                KeyCode::Enter => Key::Press(Button::Encoder),
+                KeyCode::Tab => Key::Press(Button::Page),
+                KeyCode::Char(' ') => Key::Press(Button::Play),
+                KeyCode::Char('s') => Key::Hold(Button::Play),
+                KeyCode::Char('f') => Key::Hold(Button::Encoder),
+                KeyCode::Char('b') => Key::Press(Button::Bpm),
+                KeyCode::Char('q') | KeyCode::Esc => Key::Quit,
+                _ => return Ok(Option::None),
+            },
+            _ => return Ok(Option::None),
+        };
+
+        Ok(Option::Some(key))
+    }
+
+    pub fn render(&mut self, framebuffer: &Framebuffer, gates: &[bool; 4]) -> io::Result<()> {
+        let mut line = String::with_capacity(WIDTH * 3);
+
+        for row in 0..HEIGHT / 2 {
+            line.clear();
+            for x in 0..WIDTH {
+                let top = framebuffer.pixel(x, row * 2);
+                let bottom = framebuffer.pixel(x, row * 2 + 1);
+                line.push(match (top, bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            queue!(self.stdout, MoveTo(0, row as u16), Print(&line))?;
+        }
+
+        line.clear();
+        for (name, on) in ["A", "B", "C", "D"].iter().zip(gates) {
+            let gate = if *on { '■' } else { '□' };
+            line.push_str(&format!("{} {}   ", name, gate));
+        }
+        queue!(
+            self.stdout,
+            MoveTo(0, (HEIGHT / 2 + 1) as u16),
+            Print(&line)
+        )?;
+        queue!(self.stdout, MoveTo(0, (HEIGHT / 2 + 3) as u16), Print(HELP))?;
+
+        self.stdout.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        execute!(self.stdout, Show, LeaveAlternateScreen).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}